@@ -0,0 +1,272 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::config::{StrategyConfig, TradingMode, VenueConfig};
+use crate::grid_strategy::replay_grid;
+use crate::jupiter_strategy_start::{StrategyContext, run_strategy};
+use crate::log_manager::{Trade, write_log};
+use crate::market_feed::MarketFeed;
+use crate::market_risk_analyzer::{Candle, MAX_HISTORY_LINES, load_candles, write_candles};
+use crate::portfolio::Portfolio;
+use crate::state_store::StateStore;
+use crate::strategy::build_strategy;
+use crate::swap_venue::{MockVenue, SwapVenue};
+use crate::trade_stats::{Cycle, completed_cycles};
+
+const DEFAULT_FEE_BPS: u64 = 10;
+
+/// Replays candles one by one: each `tick` of the strategy loop moves to the next candle
+pub struct BacktestFeed {
+    candles: Vec<Candle>,
+    interval_ms: i64,
+    cursor: StdMutex<usize>,
+    venue: Arc<MockVenue>,
+    left_asset: String,
+    right_asset: String,
+    trading_flag: Arc<Mutex<bool>>,
+    equity_curve: StdMutex<Vec<f64>>,
+}
+
+impl BacktestFeed {
+    fn current(&self) -> &Candle {
+        &self.candles[*self.cursor.lock().unwrap()]
+    }
+
+    async fn equity(&self) -> f64 {
+        let usdc = self.venue.balance(&self.right_asset).await.unwrap_or(0.0);
+        let held = self.venue.balance(&self.left_asset).await.unwrap_or(0.0);
+        usdc + held * self.current().close
+    }
+}
+
+#[async_trait]
+impl MarketFeed for BacktestFeed {
+    /// The strategy sees the market at the close of the current candle
+    fn now(&self) -> DateTime<Utc> {
+        let close_time = self.current().open_time + self.interval_ms;
+        DateTime::from_timestamp_millis(close_time).unwrap_or_default()
+    }
+
+    async fn refresh_history(&self, path: &str, _symbol: &str) -> Result<(), String> {
+        let cursor = *self.cursor.lock().unwrap();
+        let start = (cursor + 1).saturating_sub(MAX_HISTORY_LINES);
+        write_candles(path, &self.candles[start..=cursor])
+    }
+
+    async fn tick(&self, _duration: Duration) {
+        let equity = self.equity().await;
+        self.equity_curve.lock().unwrap().push(equity);
+
+        let next = {
+            let mut cursor = self.cursor.lock().unwrap();
+            *cursor += 1;
+            if *cursor >= self.candles.len() {
+                *cursor = self.candles.len() - 1;
+                None
+            } else {
+                Some(self.candles[*cursor].close)
+            }
+        };
+
+        match next {
+            Some(close) => self.venue.set_price(&self.left_asset, close),
+            None => *self.trading_flag.lock().await = false, // Out of candles: stop the loop
+        }
+    }
+
+    async fn retry_delay(&self, _duration: Duration) {}
+}
+
+/// Summary of a backtest run
+pub struct BacktestReport {
+    pub trades: Vec<Trade>,
+    pub candles_replayed: usize,
+    pub cycles: usize,
+    pub winning_cycles: usize,
+    pub realized_pnl: f64,
+    pub grid_round_trips: usize,
+    pub grid_profit: f64, // Sold grid lots, counted before the position fully closes
+    pub max_dca_level: u32,
+    pub starting_equity: f64,
+    pub final_equity: f64,
+    pub buy_and_hold_equity: f64,
+    pub max_drawdown_pct: f64,
+    pub open_position: f64,
+}
+
+impl BacktestReport {
+    pub fn summary(&self, pair_name: &str) -> String {
+        let pct = |value: f64| 100.0 * (value / self.starting_equity - 1.0);
+        let win_rate = if self.cycles > 0 {
+            100.0 * self.winning_cycles as f64 / self.cycles as f64
+        } else {
+            0.0
+        };
+        let grid = if self.grid_round_trips > 0 {
+            format!(
+                "🕸️ Grid: {} round trips, {:+.2} USDC\n",
+                self.grid_round_trips, self.grid_profit
+            )
+        } else {
+            String::new()
+        };
+        format!(
+            "📊 Backtest {}\n\
+             🕯️ Candles replayed: {}\n\
+             🔁 Trades: {} | Completed cycles: {} | Win rate: {:.1}%\n\
+             💰 Realized PnL: {:+.2} USDC\n\
+             {}\
+             🪜 Max DCA level: {}\n\
+             📦 Open position at end: {:.6}\n\
+             🏁 Equity: {:.2} → {:.2} USDC ({:+.2}%)\n\
+             🧺 Buy & hold: {:.2} USDC ({:+.2}%)\n\
+             📉 Max drawdown: {:.2}%",
+            pair_name,
+            self.candles_replayed,
+            self.trades.len(),
+            self.cycles,
+            win_rate,
+            self.realized_pnl,
+            grid,
+            self.max_dca_level,
+            self.open_position,
+            self.starting_equity,
+            self.final_equity,
+            pct(self.final_equity),
+            self.buy_and_hold_equity,
+            pct(self.buy_and_hold_equity),
+            self.max_drawdown_pct,
+        )
+    }
+}
+
+/// Runs the live strategy loop against a mock venue fed with historical candles
+pub async fn run_backtest(
+    config: &StrategyConfig,
+    max_drawdown_percentage: Option<f64>,
+    candles_path: &str,
+    starting_usdc: f64,
+) -> Result<BacktestReport, String> {
+    let candles = load_candles(candles_path)?;
+    if candles.len() < 2 {
+        return Err("Need at least 2 candles to backtest".to_string());
+    }
+
+    // Keep backtest state away from the live pair_* files
+    let mut bt_config = config.clone();
+    bt_config.log_dir = format!("{}/backtest", config.log_dir);
+    bt_config.mode = TradingMode::Live; // Fills are already simulated by the mock venue
+    fs::create_dir_all(&bt_config.log_dir).map_err(|e| e.to_string())?;
+    for suffix in [
+        "state.json",
+        "params.json",
+        "value.txt",
+        "dca_level.txt",
+        "trade_history.json",
+    ] {
+        let _ = fs::remove_file(bt_config.pair_log_path(suffix));
+    }
+    // The pair is the whole portfolio of the replay
+    let portfolio = max_drawdown_percentage.map(|pct| {
+        let path = bt_config.pair_log_path("portfolio_state.json");
+        let _ = fs::remove_file(&path);
+        Portfolio::new(&path, pct, &[bt_config.pair_name()])
+    });
+
+    // Start once the risk check has a full history window (or half the file if it is short)
+    let warmup = if candles.len() > 2 * MAX_HISTORY_LINES {
+        MAX_HISTORY_LINES
+    } else {
+        candles.len() / 2
+    };
+    let interval_ms = candles[1].open_time - candles[0].open_time;
+    let first_price = candles[warmup].close;
+    let last_price = candles[candles.len() - 1].close;
+
+    let fee_bps = match &config.venue {
+        VenueConfig::Mock { fee_bps, .. } => *fee_bps,
+        _ => DEFAULT_FEE_BPS,
+    };
+    let venue = Arc::new(MockVenue::new(
+        HashMap::from([
+            (config.left_asset.clone(), first_price),
+            (config.right_asset.clone(), 1.0),
+        ]),
+        HashMap::from([(config.right_asset.clone(), starting_usdc)]),
+        fee_bps,
+    ));
+
+    let trading_flag = Arc::new(Mutex::new(true));
+    let feed = BacktestFeed {
+        candles,
+        interval_ms,
+        cursor: StdMutex::new(warmup),
+        venue: venue.clone(),
+        left_asset: config.left_asset.clone(),
+        right_asset: config.right_asset.clone(),
+        trading_flag: trading_flag.clone(),
+        equity_curve: StdMutex::new(Vec::new()),
+    };
+
+    let ctx = StrategyContext {
+        config: &bt_config,
+        venue: venue.as_ref(),
+        feed: &feed,
+        notify: false,
+        portfolio: portfolio.as_ref(),
+    };
+    let mut strategy = build_strategy(&bt_config);
+    run_strategy(&ctx, strategy.as_mut(), trading_flag).await;
+
+    // === Summary stats ===
+    let trades = StateStore::open(&bt_config)
+        .and_then(|store| store.load())
+        .map_err(|e| e.to_string())?
+        .trades;
+
+    let cycles = completed_cycles(&trades).map_err(|e| e.to_string())?;
+    let realized_pnl = cycles.iter().map(Cycle::profit).fold(0.0, |sum, p| sum + p);
+    let (grid_levels, _) = replay_grid(&trades);
+    let winning_cycles = cycles.iter().filter(|cycle| cycle.profit() > 0.0).count();
+
+    let equity_curve = feed.equity_curve.lock().unwrap().clone();
+    let mut peak = starting_usdc;
+    let mut max_drawdown_pct: f64 = 0.0;
+    for equity in &equity_curve {
+        peak = peak.max(*equity);
+        max_drawdown_pct = max_drawdown_pct.max(100.0 * (1.0 - equity / peak));
+    }
+
+    let report = BacktestReport {
+        candles_replayed: equity_curve.len(),
+        cycles: cycles.len(),
+        winning_cycles,
+        realized_pnl,
+        grid_round_trips: grid_levels.iter().map(|level| level.sells).sum(),
+        grid_profit: grid_levels
+            .iter()
+            .map(|level| level.profit)
+            .fold(0.0, |sum, p| sum + p),
+        max_dca_level: trades.iter().filter_map(|t| t.dca_level).max().unwrap_or(0),
+        starting_equity: starting_usdc,
+        final_equity: feed.equity().await,
+        buy_and_hold_equity: starting_usdc / first_price * last_price,
+        max_drawdown_pct,
+        open_position: venue.balance(&config.left_asset).await.unwrap_or(0.0),
+        trades,
+    };
+
+    write_log(
+        &bt_config.pair_log_path("backtest_summary.txt"),
+        &report.summary(&config.pair_name()),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(report)
+}
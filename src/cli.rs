@@ -0,0 +1,64 @@
+use tokio::io::{AsyncBufReadExt, BufReader, Lines, Stdin};
+
+use crate::commands::{Command, CommandRunner, Reply};
+use crate::config::Role;
+use crate::manual_trade::execute;
+use crate::notifier::plain;
+
+/// Reads commands from stdin (`status`, `/pnl week`, …) with admin rights.
+/// Ends quietly when stdin is closed, e.g. when the bot runs as a service.
+pub async fn run_cli(runner: CommandRunner) {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let text = if line.starts_with('/') {
+            line.to_string()
+        } else {
+            format!("/{}", line)
+        };
+        match Command::parse(&text) {
+            Ok(command) => {
+                for reply in runner.run(command, Role::Admin).await {
+                    print_reply(reply, &mut lines).await;
+                }
+            }
+            Err(usage) => println!("{}", plain(&usage)),
+        }
+    }
+}
+
+async fn print_reply(reply: Reply, lines: &mut Lines<BufReader<Stdin>>) {
+    match reply {
+        Reply::Text(text) => println!("{}", plain(&text)),
+        Reply::Photo { png, caption } => {
+            // A new file per chart, the rendering itself keeps nothing on disk
+            let path = std::env::temp_dir().join(format!(
+                "tradeRS-chart-{}.png",
+                chrono::Utc::now().timestamp_millis()
+            ));
+            match std::fs::write(&path, png) {
+                Ok(()) => println!("📈 {} (saved as {})", caption, path.display()),
+                Err(e) => println!("📈 {} (failed to save the PNG: {})", caption, e),
+            }
+        }
+        Reply::Confirm {
+            preview,
+            pair,
+            order,
+        } => {
+            println!("{}\nConfirm? [y/N]", plain(&preview));
+            let answer = lines.next_line().await.ok().flatten().unwrap_or_default();
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                println!("❎ Cancelled `{}` for {}", order.label(), pair.name());
+                return;
+            }
+            match execute(&pair, order).await {
+                Ok(report) => println!("{}", plain(&report)),
+                Err(e) => println!("❌ `{}` failed for {}: {}", order.label(), pair.name(), e),
+            }
+        }
+    }
+}
@@ -0,0 +1,785 @@
+use chrono::Duration;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::config::{Role, StrategyConfig};
+use crate::error::{BotResult, halt_reason};
+use crate::manual_trade::{ManualOrder, preview};
+use crate::portfolio::portfolio_for;
+use crate::price_chart::{DEFAULT_TIMEFRAME, parse_timeframe, render_chart};
+use crate::reconcile::{adopt_on_chain, check_holdings};
+use crate::runtime_params::{ParamStore, TUNABLE_PARAMS, effective_config};
+use crate::state_store::StateStore;
+use crate::supervisor::{PairHandle, Supervisor};
+use crate::swap_venue::build_venue;
+use crate::token_registry::token_registry;
+use crate::trade_stats::{
+    DEFAULT_HISTORY_LEN, MAX_HISTORY_LEN, PnlPeriod, grid_report, history_report, paper_tag,
+    pnl_report,
+};
+
+/// A command every control surface (Telegram, CLI, ...) accepts
+pub struct CommandSpec {
+    pub name: &'static str,  // Without the slash, e.g. "market_status"
+    pub usage: &'static str, // Arguments, e.g. "[n] [pair]"
+    pub help: &'static str,
+    pub role: Role, // Needed to run it; each role can run the commands of the roles before it
+}
+
+const fn spec(
+    name: &'static str,
+    usage: &'static str,
+    help: &'static str,
+    role: Role,
+) -> CommandSpec {
+    CommandSpec {
+        name,
+        usage,
+        help,
+        role,
+    }
+}
+
+/// Every command, in the order `/help` lists them
+pub const COMMANDS: &[CommandSpec] = &[
+    spec("help", "", "List the commands you can run", Role::Viewer),
+    spec(
+        "status",
+        "",
+        "Online/offline state of every pair",
+        Role::Viewer,
+    ),
+    spec(
+        "start_trading",
+        "[pair]",
+        "Start one pair (or all pairs)",
+        Role::Operator,
+    ),
+    spec(
+        "stop_trading",
+        "[pair]",
+        "Stop one pair (or all pairs)",
+        Role::Operator,
+    ),
+    spec(
+        "resume",
+        "[pair]",
+        "Clear a tripped risk limit and start trading again",
+        Role::Operator,
+    ),
+    spec(
+        "market_status",
+        "[pair]",
+        "Position summary of one pair (or all pairs)",
+        Role::Viewer,
+    ),
+    spec(
+        "reconcile",
+        "[pair]",
+        "Compare the recorded holding with the wallet balance",
+        Role::Viewer,
+    ),
+    spec(
+        "adopt_onchain",
+        "[pair]",
+        "Replace the recorded holding with the wallet balance (pair must be stopped)",
+        Role::Admin,
+    ),
+    spec(
+        "history",
+        "[n] [pair]",
+        "Last n trades (default 10)",
+        Role::Viewer,
+    ),
+    spec(
+        "pnl",
+        "[day|week|month|all] [pair]",
+        "Realized PnL per cycle and unrealized PnL of the open position",
+        Role::Viewer,
+    ),
+    spec(
+        "chart",
+        "[12h|3d|2w] [pair]",
+        "Price chart with our trades, average entry, take profit and next DCA",
+        Role::Viewer,
+    ),
+    spec(
+        "grid",
+        "[pair]",
+        "Fills and profit of each grid level and the open lots",
+        Role::Viewer,
+    ),
+    spec("get", "[pair]", "Current strategy parameters", Role::Viewer),
+    spec(
+        "set",
+        "<param> <value> [pair]",
+        "Change a parameter at runtime",
+        Role::Admin,
+    ),
+    spec(
+        "buy",
+        "<amount> [pair]",
+        "Buy with <amount> of the quote asset after a preview",
+        Role::Admin,
+    ),
+    spec(
+        "sell",
+        "<percentage>% [pair]",
+        "Sell part of the recorded position after a preview",
+        Role::Admin,
+    ),
+    spec(
+        "panic_sell",
+        "[pair]",
+        "Sell the whole position and keep trading stopped",
+        Role::Admin,
+    ),
+];
+
+/// Looks a command up by name, with or without the leading slash
+pub fn find(name: &str) -> Option<&'static CommandSpec> {
+    let name = name.trim_start_matches('/');
+    COMMANDS.iter().find(|spec| spec.name == name)
+}
+
+/// The commands `role` can run, one `/name usage — help` line each
+pub fn help_text(role: Role) -> String {
+    let mut lines = vec!["📖 *Commands*".to_string()];
+    for spec in COMMANDS.iter().filter(|spec| spec.role <= role) {
+        let usage = if spec.usage.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", spec.usage)
+        };
+        lines.push(format!("/{}{} — {}", spec.name, usage, spec.help).replace('_', "\\_"));
+    }
+    lines.join("\n")
+}
+
+/// A parsed command, ready to run
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    Status,
+    StartTrading(Option<String>),
+    StopTrading(Option<String>),
+    Resume(Option<String>),
+    MarketStatus(Option<String>),
+    Reconcile(Option<String>),
+    AdoptOnchain(Option<String>),
+    History {
+        count: usize,
+        pair: Option<String>,
+    },
+    Pnl {
+        period: PnlPeriod,
+        pair: Option<String>,
+    },
+    Chart {
+        window: Duration,
+        pair: Option<String>,
+    },
+    Grid(Option<String>),
+    Get(Option<String>),
+    Set {
+        name: String,
+        value: String,
+        pair: Option<String>,
+    },
+    Trade {
+        order: ManualOrder,
+        pair: Option<String>,
+    },
+}
+
+impl Command {
+    /// Parses e.g. `/history 5 SOL/USDC`; the error is the reply to send back
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parts = text.split_whitespace();
+        // `/status@my_bot` in group chats
+        let name = parts.next().unwrap_or_default();
+        let name = name.split('@').next().unwrap_or_default();
+        let args: Vec<&str> = parts.collect();
+        let Some(spec) = find(name) else {
+            return Err(format!("Unknown command `{}`, see /help", name));
+        };
+        let pair = |idx: usize| args.get(idx).map(|arg| arg.to_string());
+
+        Ok(match spec.name {
+            "help" => Command::Help,
+            "status" => Command::Status,
+            "start_trading" => Command::StartTrading(pair(0)),
+            "stop_trading" => Command::StopTrading(pair(0)),
+            "resume" => Command::Resume(pair(0)),
+            "market_status" => Command::MarketStatus(pair(0)),
+            "reconcile" => Command::Reconcile(pair(0)),
+            "adopt_onchain" => Command::AdoptOnchain(pair(0)),
+            "history" => match args.first().and_then(|a| a.parse::<usize>().ok()) {
+                Some(count) => Command::History {
+                    count: count.clamp(1, MAX_HISTORY_LEN),
+                    pair: pair(1),
+                },
+                None => Command::History {
+                    count: DEFAULT_HISTORY_LEN,
+                    pair: pair(0),
+                },
+            },
+            "pnl" => match args.first().and_then(|a| PnlPeriod::parse(a)) {
+                Some(period) => Command::Pnl {
+                    period,
+                    pair: pair(1),
+                },
+                None => Command::Pnl {
+                    period: PnlPeriod::All,
+                    pair: pair(0),
+                },
+            },
+            "chart" => match args.first().and_then(|a| parse_timeframe(a)) {
+                Some(window) => Command::Chart {
+                    window,
+                    pair: pair(1),
+                },
+                None => Command::Chart {
+                    window: parse_timeframe(DEFAULT_TIMEFRAME).unwrap_or_default(),
+                    pair: pair(0),
+                },
+            },
+            "grid" => Command::Grid(pair(0)),
+            "get" => Command::Get(pair(0)),
+            "set" if args.len() < 2 => {
+                return Err(format!(
+                    "Usage: /set <param> <value> [pair]\nParams: {}",
+                    TUNABLE_PARAMS.map(|p| format!("`{p}`")).join(", ")
+                ));
+            }
+            "set" => Command::Set {
+                name: args[0].to_string(),
+                value: args[1].to_string(),
+                pair: pair(2),
+            },
+            _ => {
+                let (order, pair) = ManualOrder::parse(&format!("/{}", spec.name), &args)?;
+                Command::Trade {
+                    order,
+                    pair: pair.map(str::to_string),
+                }
+            }
+        })
+    }
+}
+
+/// What a command answers, rendered by each surface in its own way
+pub enum Reply {
+    Text(String),
+    Photo {
+        png: Vec<u8>,
+        caption: String,
+    },
+    /// A manual trade preview, run only once the caller confirms it
+    Confirm {
+        preview: String,
+        pair: Arc<PairHandle>,
+        order: ManualOrder,
+    },
+}
+
+/// Runs parsed commands against the supervisor; callers check the role first
+#[derive(Clone)]
+pub struct CommandRunner {
+    supervisor: Arc<Supervisor>,
+}
+
+impl CommandRunner {
+    pub fn new(supervisor: Arc<Supervisor>) -> Self {
+        Self { supervisor }
+    }
+
+    /// `role` only filters `/help`
+    pub async fn run(&self, command: Command, role: Role) -> Vec<Reply> {
+        let supervisor = &self.supervisor;
+        match command {
+            Command::Help => vec![Reply::Text(help_text(role))],
+            Command::Status => vec![Reply::Text(supervisor.status_report().await)],
+            Command::StartTrading(_) | Command::Resume(_) if let Some(reason) = halt_reason() => {
+                vec![Reply::Text(format!(
+                    "🚨 Bot halted: {reason}\nCheck the pair state files and restart the bot."
+                ))]
+            }
+            Command::StartTrading(pair) => {
+                self.for_pairs(pair, |pair| async move {
+                    match StateStore::open(&pair.config).and_then(|store| store.load()) {
+                        Ok(ref state) if let Some(breaker) = state.active_breaker() => {
+                            Reply::Text(format!(
+                                "⛔ *{}* was stopped by a risk limit: {}\nUse /resume to trade again.",
+                                pair.name(),
+                                breaker.reason
+                            ))
+                        }
+                        Ok(_) => {
+                            supervisor.set_trading(&pair, true).await;
+                            Reply::Text(format!("✅ Trading Started: *{}*", pair.name()))
+                        }
+                        Err(e) => Reply::Text(format!(
+                            "❌ Could not read the state of {}: {e}",
+                            pair.name()
+                        )),
+                    }
+                })
+                .await
+            }
+            Command::Resume(pair) => {
+                self.for_pairs(pair, |pair| async move {
+                    // The pair is stopped, so its task does not write the state meanwhile
+                    let resumed = StateStore::open(&pair.config).and_then(|store| {
+                        let mut state = store.load()?;
+                        let reason = state.active_breaker().map(|b| b.reason.clone());
+                        if reason.is_some() {
+                            state.resume();
+                            store.save(&state)?;
+                            // A portfolio drawdown is measured again from the current equity
+                            if let Some(portfolio) = portfolio_for(&pair.config) {
+                                portfolio.resume()?;
+                            }
+                        }
+                        Ok(reason)
+                    });
+                    match resumed {
+                        Ok(Some(reason)) => {
+                            supervisor.set_trading(&pair, true).await;
+                            Reply::Text(format!(
+                                "▶️ Trading Resumed: *{}* (stopped by: {})",
+                                pair.name(),
+                                reason
+                            ))
+                        }
+                        Ok(None) => Reply::Text(format!(
+                            "⚠️ No tripped risk limit on *{}*, use /start\\_trading",
+                            pair.name()
+                        )),
+                        Err(e) => Reply::Text(format!(
+                            "❌ Could not resume {}: {e}",
+                            pair.name()
+                        )),
+                    }
+                })
+                .await
+            }
+            Command::StopTrading(pair) => {
+                self.for_pairs(pair, |pair| async move {
+                    if supervisor.set_trading(&pair, false).await {
+                        Reply::Text(format!("🛑 Safe Stop Triggered: *{}*", pair.name()))
+                    } else {
+                        Reply::Text(format!("⚠️ Trading already stopped: *{}*", pair.name()))
+                    }
+                })
+                .await
+            }
+            Command::MarketStatus(pair) => {
+                self.for_pairs(pair, |pair| async move {
+                    Reply::Text(match generate_market_status(&pair.config).await {
+                        Ok(summary) => summary,
+                        Err(e) => {
+                            format!("❌ Failed to get market status for {}: {e}", pair.name())
+                        }
+                    })
+                })
+                .await
+            }
+            Command::Reconcile(pair) => {
+                let configs = &supervisor.configs();
+                self.for_pairs(pair, |pair| async move {
+                    Reply::Text(match check_holdings(&pair.config, configs).await {
+                        Ok(reconciliation) => reconciliation.report(&pair.config).await,
+                        Err(e) => format!("❌ Could not reconcile {}: {e}", pair.name()),
+                    })
+                })
+                .await
+            }
+            Command::AdoptOnchain(pair) => {
+                let configs = &supervisor.configs();
+                self.for_pairs(pair, |pair| async move {
+                    // Waits for the current strategy iteration, which could still record a fill
+                    let _busy = pair.busy.lock().await;
+                    // The strategy would overwrite the holding on its next fill
+                    if *pair.trading_flag.lock().await {
+                        return Reply::Text(format!(
+                            "⚠️ Stop trading *{}* before adopting the wallet balance",
+                            pair.name()
+                        ));
+                    }
+                    Reply::Text(match adopt_on_chain(&pair.config, configs).await {
+                        Ok((reconciliation, state)) => format!(
+                            "🔄 *{}*: holding set from {:.6} to {:.6} (DCA level {})",
+                            pair.name(),
+                            reconciliation.recorded,
+                            state.holding,
+                            state.dca_level
+                        ),
+                        Err(e) => format!(
+                            "❌ Could not adopt the wallet balance for {}: {e}",
+                            pair.name()
+                        ),
+                    })
+                })
+                .await
+            }
+            Command::History { count, pair } => {
+                self.for_pairs(pair, |pair| async move {
+                    Reply::Text(
+                        history_report(&pair.config, count)
+                            .await
+                            .unwrap_or_else(|e| {
+                                format!("❌ Could not read the trades of {}: {e}", pair.name())
+                            }),
+                    )
+                })
+                .await
+            }
+            Command::Pnl { period, pair } => {
+                self.for_pairs(pair, |pair| async move {
+                    Reply::Text(pnl_report(&pair.config, period).await.unwrap_or_else(|e| {
+                        format!("❌ Could not compute the PnL of {}: {e}", pair.name())
+                    }))
+                })
+                .await
+            }
+            Command::Chart { window, pair } => {
+                self.for_pairs(pair, |pair| async move {
+                    match render_chart(&pair.config, window).await {
+                        Ok((png, caption)) => Reply::Photo { png, caption },
+                        Err(e) => Reply::Text(format!(
+                            "❌ Could not draw the chart of {}: {e}",
+                            pair.name()
+                        )),
+                    }
+                })
+                .await
+            }
+            Command::Grid(pair) => {
+                self.for_pairs(pair, |pair| async move {
+                    Reply::Text(grid_report(&pair.config).await.unwrap_or_else(|e| {
+                        format!("❌ Could not read the grid of {}: {e}", pair.name())
+                    }))
+                })
+                .await
+            }
+            Command::Get(pair) => {
+                self.for_pairs(pair, |pair| async move {
+                    Reply::Text(match params_summary(&pair.config) {
+                        Ok(summary) => summary,
+                        Err(e) => {
+                            format!("❌ Could not read the parameters of {}: {e}", pair.name())
+                        }
+                    })
+                })
+                .await
+            }
+            Command::Set { name, value, pair } => {
+                let (name, value) = (name.as_str(), value.as_str());
+                self.for_pairs(pair, |pair| async move {
+                    let store = ParamStore::open(&pair.config);
+                    Reply::Text(match store.set(&pair.config, name, value) {
+                        Ok(effective) => format!(
+                            "⚙️ *{}*: `{}` set to {} (applies from the next iteration)",
+                            pair.name(),
+                            name,
+                            effective.get_param(name).unwrap_or_default()
+                        ),
+                        Err(e) => format!("❌ Could not set `{}` for {}: {e}", name, pair.name()),
+                    })
+                })
+                .await
+            }
+            // A trade is never sent to every pair at once
+            Command::Trade { order, pair: None } if supervisor.configs().len() > 1 => {
+                let command = order.label();
+                let command = command.split_whitespace().next().unwrap_or_default();
+                let usage = find(command).map_or("", |spec| spec.usage);
+                let pairs: Vec<String> = supervisor.configs().iter().map(|c| c.pair_name()).collect();
+                vec![Reply::Text(
+                    format!(
+                        "❌ Several pairs are configured, name one.\nUsage: {} {}\nPairs: {}",
+                        command,
+                        usage.replace("[pair]", "<pair>"),
+                        pairs.join(", ")
+                    )
+                    .replace('_', "\\_"),
+                )]
+            }
+            Command::Trade { order, pair } => {
+                self.for_pairs(pair, |pair| async move {
+                    match preview(&pair.config, order).await {
+                        Ok(preview) => Reply::Confirm {
+                            preview,
+                            pair,
+                            order,
+                        },
+                        Err(e) => Reply::Text(format!(
+                            "❌ Could not prepare `{}` for {}: {e}",
+                            order.label(),
+                            pair.name()
+                        )),
+                    }
+                })
+                .await
+            }
+        }
+    }
+
+    /// One reply per selected pair (all pairs when none is named)
+    async fn for_pairs<F, Fut>(&self, pair: Option<String>, reply: F) -> Vec<Reply>
+    where
+        F: Fn(Arc<PairHandle>) -> Fut,
+        Fut: Future<Output = Reply>,
+    {
+        match self.supervisor.select(pair.as_deref()) {
+            Ok(pairs) => {
+                let mut replies = Vec::new();
+                for pair in pairs {
+                    replies.push(reply(pair).await);
+                }
+                replies
+            }
+            Err(e) => vec![Reply::Text(format!("❌ {e}"))],
+        }
+    }
+}
+
+async fn generate_market_status(
+    config: &StrategyConfig,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let left_asset = config.left_asset.as_str();
+    let right_asset = config.right_asset.as_str();
+    let effective = effective_config(config)?;
+    let sell_percentage = effective.sell_percentage;
+
+    let state = StateStore::open(config)?.load()?;
+    let sol_holding = state.holding;
+    let paid_usdc = state.open_cost();
+
+    // Quoted on the pair's own venue, like the strategy does; nothing to quote without a holding
+    let usdc_received = if sol_holding > 0.0 {
+        build_venue(config)?
+            .quote(left_asset, right_asset, sol_holding, 50)
+            .await?
+            .out_amount
+    } else {
+        0.0
+    };
+    let left_symbol = token_registry().symbol(left_asset).await;
+    let right_symbol = token_registry().symbol(right_asset).await;
+    let exit_quote = if sol_holding > 0.0 {
+        format!(
+            "🔁 Would return {:.6} {} for selling {:.6} {}",
+            usdc_received, right_symbol, sol_holding, left_symbol
+        )
+    } else {
+        "🔁 No open position to sell".to_string()
+    };
+
+    let target_return = paid_usdc * (1.0 + sell_percentage / 100.0);
+    let price_change = if paid_usdc > 0.0 {
+        100.0 * (usdc_received / paid_usdc - 1.0)
+    } else {
+        0.0
+    };
+
+    let mode = paper_tag(config);
+
+    let trailing = match state.trailing_peak {
+        Some(peak) if effective.trail_percentage > 0.0 && sol_holding > 0.0 => {
+            let floor = (peak * (1.0 - effective.trail_percentage / 100.0))
+                .max(target_return / sol_holding);
+            format!(
+                "\n🪁 Trailing: peak {:.6}, sells below {:.6} {} per {}",
+                peak, floor, right_symbol, left_symbol
+            )
+        }
+        _ => String::new(),
+    };
+    // The higher of the two stop-loss limits is hit first
+    let stop_loss = [
+        effective
+            .stop_loss_percentage
+            .map(|pct| paid_usdc * (1.0 - pct / 100.0)),
+        effective.stop_loss_amount.map(|amount| paid_usdc - amount),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(f64::max);
+    let stop_loss = match stop_loss {
+        Some(exit) if sol_holding > 0.0 => {
+            format!(
+                "\n🛑 Stop-loss when it returns {:.6} {} or less",
+                exit, right_symbol
+            )
+        }
+        _ => String::new(),
+    };
+    let ladder = match &state.ladder {
+        Some(ladder) if sol_holding > 0.0 => match ladder.steps.get(state.dca_level as usize) {
+            Some(step) => format!(
+                "\n🪜 Next DCA level {}/{}: {:.2} {} at {:.6}",
+                state.dca_level + 1,
+                ladder.steps.len(),
+                step.spend,
+                right_symbol,
+                step.trigger_price
+            ),
+            None => format!("\n🪜 All {} ladder levels used", ladder.steps.len()),
+        },
+        _ => String::new(),
+    };
+
+    Ok(format!(
+        "📊 *{}*{}\n\
+         🔁 Holding: {:.6} {} →\n\
+         {}\n\
+         🎯 Need at least {:.6} {} to sell for profit (+{:.1}%)\n\
+         📉 Price is at {:+.2}%{}{}{}",
+        config.pair_name(),
+        mode,
+        sol_holding,
+        left_symbol,
+        exit_quote,
+        target_return,
+        right_symbol,
+        sell_percentage,
+        price_change,
+        trailing,
+        stop_loss,
+        ladder
+    ))
+}
+
+/// Effective value of every runtime parameter, marking the ones changed with /set
+fn params_summary(config: &StrategyConfig) -> BotResult<String> {
+    let overrides = ParamStore::open(config).load()?;
+    let effective = effective_config(config)?;
+    let mut lines = vec![format!("⚙️ *{}* parameters:", config.pair_name())];
+    for name in TUNABLE_PARAMS {
+        let value = effective.get_param(name).unwrap_or_default();
+        let marker = if overrides.contains_key(name) {
+            " ✏️"
+        } else {
+            ""
+        };
+        lines.push(format!("`{}` = {}{}", name, value, marker));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    #[test]
+    fn parses_arguments_and_optional_pairs() {
+        assert_eq!(Command::parse("/status@my_bot"), Ok(Command::Status));
+        assert_eq!(
+            Command::parse("start_trading SOL/USDC"),
+            Ok(Command::StartTrading(Some("SOL/USDC".to_string())))
+        );
+        assert_eq!(
+            Command::parse("/history 500"),
+            Ok(Command::History {
+                count: MAX_HISTORY_LEN,
+                pair: None
+            })
+        );
+        // A first argument that is not a count is the pair
+        assert_eq!(
+            Command::parse("/history sol"),
+            Ok(Command::History {
+                count: DEFAULT_HISTORY_LEN,
+                pair: Some("sol".to_string())
+            })
+        );
+        assert_eq!(
+            Command::parse("/pnl WEEK sol"),
+            Ok(Command::Pnl {
+                period: PnlPeriod::Week,
+                pair: Some("sol".to_string())
+            })
+        );
+        assert_eq!(
+            Command::parse("/chart"),
+            Ok(Command::Chart {
+                window: Duration::days(3),
+                pair: None
+            })
+        );
+        assert_eq!(
+            Command::parse("/set sell_percentage 2.5 sol"),
+            Ok(Command::Set {
+                name: "sell_percentage".to_string(),
+                value: "2.5".to_string(),
+                pair: Some("sol".to_string())
+            })
+        );
+        assert_eq!(
+            Command::parse("/sell 50% sol"),
+            Ok(Command::Trade {
+                order: ManualOrder::Sell(50.0),
+                pair: Some("sol".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_commands_and_missing_arguments() {
+        assert!(
+            Command::parse("/moon")
+                .unwrap_err()
+                .contains("Unknown command")
+        );
+        assert!(Command::parse("").is_err());
+        assert!(
+            Command::parse("/set sell_percentage")
+                .unwrap_err()
+                .starts_with("Usage")
+        );
+        assert!(Command::parse("/buy -5").unwrap_err().starts_with("Usage"));
+        assert!(
+            Command::parse("/sell 150%")
+                .unwrap_err()
+                .starts_with("Usage")
+        );
+    }
+
+    #[tokio::test]
+    async fn trades_name_the_pair_when_several_are_configured() {
+        let usdc = test_config("trade-pair-usdc", "");
+        let mut usdt = test_config("trade-pair-usdt", "");
+        usdt.right_asset = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string();
+        let runner = CommandRunner::new(Arc::new(
+            Supervisor::start(vec![usdc.clone(), usdt.clone()]).await,
+        ));
+
+        let replies = runner
+            .run(Command::parse("/buy 50").unwrap(), Role::Admin)
+            .await;
+        match replies.as_slice() {
+            [Reply::Text(text)] => {
+                assert!(text.contains("Usage: /buy <amount> <pair>"), "{text}");
+            }
+            _ => panic!("expected a single usage reply"),
+        }
+    }
+
+    #[test]
+    fn help_lists_only_the_commands_of_the_role() {
+        let viewer = help_text(Role::Viewer);
+        assert!(viewer.contains("/market\\_status"));
+        assert!(!viewer.contains("/start\\_trading"));
+        assert!(!viewer.contains("/buy"));
+
+        let operator = help_text(Role::Operator);
+        assert!(operator.contains("/start\\_trading"));
+        assert!(!operator.contains("/set"));
+
+        let admin = help_text(Role::Admin);
+        assert_eq!(admin.lines().count(), COMMANDS.len() + 1);
+    }
+}
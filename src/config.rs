@@ -0,0 +1,1056 @@
+use chrono::{NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+/// Default location of the strategy config, overridable with `TRADERS_CONFIG`
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+/// Top level tables that are not part of a pair
+const BOT_TABLES: [&str; 4] = ["telegram", "notifiers", "digest", "risk"];
+
+/// Top level config: one entry per trading pair run by the supervisor
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BotConfig {
+    pub pairs: Vec<StrategyConfig>,
+    #[serde(default)]
+    pub telegram: TelegramConfig,
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>, // Empty = Telegram only
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub risk: RiskConfig,
+}
+
+/// Limits over all the pairs together, e.g. `[risk]` with `max_drawdown_percentage = 25.0`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiskConfig {
+    pub max_drawdown_percentage: Option<f64>, // Exit every pair once the summed equity is this far (%) below its peak
+}
+
+/// When the performance summaries are sent, e.g. `daily = "08:00"`, `weekly = "mon 08:00"`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DigestConfig {
+    #[serde(default = "default_digest_timezone")]
+    pub timezone: String, // IANA name, e.g. "Europe/Rome"
+    pub daily: Option<String>,  // "HH:MM", no daily digest when missing
+    pub weekly: Option<String>, // "<weekday> HH:MM", no weekly digest when missing
+}
+
+fn default_digest_timezone() -> String {
+    "UTC".to_string()
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            timezone: default_digest_timezone(),
+            daily: None,
+            weekly: None,
+        }
+    }
+}
+
+impl DigestConfig {
+    pub fn tz(&self) -> Result<chrono_tz::Tz, String> {
+        self.timezone
+            .parse()
+            .map_err(|_| "Invalid config: `digest.timezone` is not an IANA time zone".to_string())
+    }
+
+    pub fn daily_time(&self) -> Result<Option<NaiveTime>, String> {
+        self.daily
+            .as_deref()
+            .map(|time| {
+                NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| "Invalid config: `digest.daily` must be \"HH:MM\"".to_string())
+            })
+            .transpose()
+    }
+
+    pub fn weekly_time(&self) -> Result<Option<(Weekday, NaiveTime)>, String> {
+        self.weekly
+            .as_deref()
+            .map(|value| {
+                let (day, time) = value.split_once(' ').unwrap_or((value, ""));
+                match (day.parse::<Weekday>(), NaiveTime::parse_from_str(time.trim(), "%H:%M")) {
+                    (Ok(day), Ok(time)) => Ok((day, time)),
+                    _ => Err(
+                        "Invalid config: `digest.weekly` must be \"<weekday> HH:MM\", e.g. \"mon 08:00\""
+                            .to_string(),
+                    ),
+                }
+            })
+            .transpose()
+    }
+}
+
+/// Where alerts and trade notifications go, e.g. `[[notifiers]]` with `kind = "discord"`
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifierConfig {
+    #[serde(flatten)]
+    pub kind: NotifierKind,
+    #[serde(default)]
+    pub min_severity: Severity, // Messages below this level are not sent
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NotifierKind {
+    Telegram,
+    Webhook { url: String }, // POSTs `{"severity", "message", "time"}`
+    Discord { url: String },
+    Slack { url: String },
+    Stdout,
+    File { path: String }, // One line per message
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info, // Trades, start / stop
+    Warning,  // Paused pairs, failed checks
+    Critical, // The bot halted
+}
+
+/// Who may command the bot from Telegram, e.g. `[[telegram.users]]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelegramConfig {
+    #[serde(default)]
+    pub users: Vec<TelegramUser>, // Empty = anyone in `TELEGRAM_CHAT_ID` is an admin
+    pub webhook: Option<WebhookConfig>, // Polls getUpdates when missing
+}
+
+/// Receives updates on an embedded server instead of polling, e.g. `[telegram.webhook]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String, // Public HTTPS URL given to Telegram, e.g. "https://bot.example.com/telegram"
+    #[serde(default = "default_webhook_listen")]
+    pub listen: String, // Local address of the server
+    pub tls_cert: Option<String>, // PEM certificate chain, plain HTTP (behind a proxy) when missing
+    pub tls_key: Option<String>, // PEM private key
+}
+
+fn default_webhook_listen() -> String {
+    "0.0.0.0:8443".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelegramUser {
+    pub id: i64,              // Telegram user id (not the chat id)
+    pub name: Option<String>, // Shown in the audit log
+    pub role: Role,
+}
+
+/// Each role can also run the commands of the roles before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,   // Status, market status, PnL, history, parameters
+    Operator, // Start / stop trading
+    Admin,    // Change parameters, adopt balances, manual trades
+}
+
+/// All the parameters needed to run one trading pair
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyConfig {
+    pub name: Option<String>, // Label used in Telegram commands (defaults to the mint prefixes)
+    pub left_asset: String,   // Token we accumulate (e.g. SOL mint)
+    pub right_asset: String,  // Quote token we spend (e.g. USDC mint)
+    #[serde(default = "default_binance_symbol")]
+    pub binance_symbol: String, // Symbol used for the market risk check (e.g. SOLUSDT)
+
+    pub sell_percentage: f64, // Sell percentage (The Profit Percentage)
+    pub dca_recover_percentage: f64, // Percentage drop that triggers a DCA buy
+    pub r_factor: f64,        // Fraction of the capital to use on each DCA buy
+    #[serde(default)]
+    pub trail_percentage: f64, // 0 = sell at the target, otherwise trail the peak by this much
+
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: i64, // Pause after a sell before buying again
+    #[serde(default = "default_trade_retries")]
+    pub trade_retries: u32, // Max swap attempts per trade
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: u64, // Slippage used on the first swap attempt
+    #[serde(default = "default_slippage_bps_max")]
+    pub slippage_bps_max: u64, // Slippage is raised by 1bps on each retry up to this value
+
+    #[serde(default = "default_min_buy_amount")]
+    pub min_buy_amount: f64, // Minimum USDC amount for the first buy
+    #[serde(default = "default_min_dca_amount")]
+    pub min_dca_amount: f64, // Minimum USDC amount for a DCA buy
+
+    pub stop_loss_percentage: Option<f64>, // Exit once the open position is down this much (%)
+    pub stop_loss_amount: Option<f64>, // Exit once the open position has lost this much `right_asset`
+    pub allocated_capital: Option<f64>, // `right_asset` budget of the pair, the base of its equity
+    pub max_dca_level: Option<u32>,    // Pause instead of averaging down past this DCA level
+
+    #[serde(default = "default_reconcile_tolerance")]
+    pub reconcile_tolerance: f64, // Accepted gap between recorded and wallet `left_asset` amounts
+    #[serde(default = "default_sol_fee_reserve")]
+    pub sol_fee_reserve: f64, // Native SOL kept for fees and rent, never part of a SOL pair's holding
+
+    #[serde(default = "default_rpc_url")]
+    pub rpc_url: String, // Used when `rpc_endpoints` is empty
+    #[serde(default)]
+    pub rpc_endpoints: Vec<RpcEndpointConfig>, // Failover list, e.g. `[[pairs.rpc_endpoints]]`
+    #[serde(default = "default_rpc_health_interval_secs")]
+    pub rpc_health_interval_secs: u64, // How often each endpoint is probed
+    #[serde(default = "default_rpc_max_slot_lag")]
+    pub rpc_max_slot_lag: u64, // Endpoints further behind the best slot are skipped
+    #[serde(default = "default_rpc_max_latency_ms")]
+    pub rpc_max_latency_ms: u64, // Endpoints slower than this are skipped
+    #[serde(default = "default_log_dir")]
+    pub log_dir: String,
+
+    #[serde(default)]
+    pub autostart: bool, // Start trading at launch instead of waiting for /start_trading
+
+    #[serde(default)]
+    pub strategy: StrategyKind, // Trading rules (defaults to DCA)
+    pub dca_ladder: Option<DcaLadderConfig>, // Explicit DCA levels instead of `dca_recover_percentage` / `r_factor`
+    #[serde(default)]
+    pub venue: VenueConfig, // Where swaps are executed (defaults to Jupiter)
+    #[serde(default)]
+    pub mode: TradingMode, // Live trading or paper trading on real quotes
+    #[serde(default)]
+    pub paper: PaperConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradingMode {
+    #[default]
+    Live,
+    Paper,
+}
+
+/// Simulated account used when `mode = "paper"`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PaperConfig {
+    #[serde(default = "default_paper_starting_balance")]
+    pub starting_balance: f64, // Virtual `right_asset` balance (e.g. USDC)
+    #[serde(default = "default_paper_fee_bps")]
+    pub fee_bps: u64, // Modeled fee taken from each simulated fill
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            starting_balance: default_paper_starting_balance(),
+            fee_bps: default_paper_fee_bps(),
+        }
+    }
+}
+
+/// One Solana RPC endpoint of the failover pool
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcEndpointConfig {
+    pub url: String,
+    #[serde(default)]
+    pub priority: u32, // Lower is preferred
+    #[serde(default)]
+    pub headers: HashMap<String, String>, // e.g. an auth token header
+}
+
+/// Trading rules of a pair, e.g. `[pairs.strategy]` with `kind = "grid"`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum StrategyKind {
+    #[default]
+    Dca,
+    Grid {
+        lower: Option<f64>, // Lowest level; both bounds unset = derived from the most-touched prices
+        upper: Option<f64>, // Highest level, only a sell target
+        #[serde(default = "default_grid_levels")]
+        levels: u32, // Number of levels, bounds included
+        order_size: Option<f64>, // `right_asset` per lot (unset = balance split over the free lots)
+    },
+}
+
+/// DCA levels planned when a position opens, e.g. `[pairs.dca_ladder]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DcaLadderConfig {
+    #[serde(default)]
+    pub levels: Vec<LadderLevelConfig>, // Explicit levels, shallowest first
+    pub geometric: Option<GeometricLadderConfig>, // Generated levels instead of `levels`
+    pub max_levels: Option<u32>, // Levels used (defaults to all of `levels`, required with `geometric`)
+    #[serde(default)]
+    pub min_spacing_secs: i64, // Minimum time between two buys of the position
+}
+
+/// A level `drop` % below the entry price, buying a fixed `amount` or a share of the opening buy
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LadderLevelConfig {
+    pub drop: f64,
+    pub amount: Option<f64>,           // `right_asset` to spend
+    pub entry_percentage: Option<f64>, // Spend as % of the buy that opened the position
+}
+
+/// Level n triggers `n * drop` % below the entry price and spends
+/// `entry_percentage * ratio^(n - 1)` % of the opening buy
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeometricLadderConfig {
+    pub drop: f64,
+    pub entry_percentage: f64,
+    pub ratio: f64,
+}
+
+/// Swap venue used by a pair, e.g. `[pairs.venue]` with `kind = "jupiter"`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum VenueConfig {
+    #[default]
+    Jupiter,
+    Osmosis {
+        pool_id: String,
+        address: String, // Must be the address of `OSMO_WALLET_MNEMONIC` for live swaps
+        #[serde(default)]
+        decimals: HashMap<String, u8>, // Decimals of denoms missing from the built-in table
+    },
+    Mock {
+        #[serde(default)]
+        prices: HashMap<String, f64>, // Price of each mint in a common unit (missing = 1.0)
+        #[serde(default)]
+        balances: HashMap<String, f64>, // Starting balances in UI units
+        #[serde(default)]
+        fee_bps: u64,
+    },
+}
+
+fn default_binance_symbol() -> String {
+    "SOLUSDT".to_string()
+}
+
+fn default_grid_levels() -> u32 {
+    10
+}
+
+fn default_cooldown_secs() -> i64 {
+    3600 // 1 hour
+}
+
+fn default_trade_retries() -> u32 {
+    200
+}
+
+fn default_slippage_bps() -> u64 {
+    1
+}
+
+fn default_slippage_bps_max() -> u64 {
+    5
+}
+
+fn default_min_buy_amount() -> f64 {
+    10.0
+}
+
+fn default_min_dca_amount() -> f64 {
+    5.0
+}
+
+fn default_reconcile_tolerance() -> f64 {
+    0.001
+}
+
+fn default_sol_fee_reserve() -> f64 {
+    0.05
+}
+
+fn default_rpc_url() -> String {
+    "https://api.mainnet-beta.solana.com".to_string()
+}
+
+fn default_rpc_health_interval_secs() -> u64 {
+    30
+}
+
+fn default_rpc_max_slot_lag() -> u64 {
+    50
+}
+
+fn default_rpc_max_latency_ms() -> u64 {
+    2000
+}
+
+fn default_log_dir() -> String {
+    "logs/solana".to_string()
+}
+
+fn default_paper_starting_balance() -> f64 {
+    1000.0
+}
+
+fn default_paper_fee_bps() -> u64 {
+    10
+}
+
+impl BotConfig {
+    /// Loads the config from `TRADERS_CONFIG` (or `config.toml`) and validates it
+    pub fn load() -> Result<Self, String> {
+        let path = env::var("TRADERS_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        Self::from_file(&path)
+    }
+
+    /// Accepts either a list of `[[pairs]]` or a single pair defined at the top level
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path, e))?;
+        let value: toml::Value = toml::from_str(&contents)
+            .map_err(|e| format!("Failed to parse config file {}: {}", path, e))?;
+
+        // A single pair can be defined at the top level, next to the bot-wide tables
+        let value = match value {
+            toml::Value::Table(mut table) if !table.contains_key("pairs") => {
+                let mut bot = toml::Table::new();
+                for key in BOT_TABLES {
+                    if let Some(section) = table.remove(key) {
+                        bot.insert(key.to_string(), section);
+                    }
+                }
+                bot.insert(
+                    "pairs".to_string(),
+                    toml::Value::Array(vec![toml::Value::Table(table)]),
+                );
+                toml::Value::Table(bot)
+            }
+            value => value,
+        };
+        let config: BotConfig = value
+            .try_into()
+            .map_err(|e| format!("Failed to parse config file {}: {}", path, e))?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pairs.is_empty() {
+            return Err("Invalid config: `pairs` must contain at least one pair".to_string());
+        }
+
+        if let Some(pct) = self.risk.max_drawdown_percentage
+            && !(pct > 0.0 && pct < 100.0)
+        {
+            return Err(
+                "Invalid config: `risk.max_drawdown_percentage` must be in (0, 100)".to_string(),
+            );
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        for (idx, pair) in self.pairs.iter().enumerate() {
+            pair.validate()
+                .map_err(|e| e.replacen("`", &format!("`pairs[{}].", idx), 1))?;
+            if self.risk.max_drawdown_percentage.is_some() && pair.allocated_capital.is_none() {
+                return Err(format!(
+                    "Invalid config: `pairs[{}].allocated_capital` is required with `risk.max_drawdown_percentage`",
+                    idx
+                ));
+            }
+
+            let name = pair.pair_name();
+            if names.contains(&name) {
+                return Err(format!(
+                    "Invalid config: `pairs[{}].name` duplicates pair {}",
+                    idx, name
+                ));
+            }
+            names.push(name);
+
+            // State files are named after the assets, so two pairs on them would share a position
+            if let Some(other) = self.pairs[..idx].iter().find(|other| {
+                other.left_asset == pair.left_asset && other.right_asset == pair.right_asset
+            }) {
+                return Err(format!(
+                    "Invalid config: `pairs[{}].right_asset` duplicates the assets of pair {}",
+                    idx,
+                    other.pair_name()
+                ));
+            }
+        }
+
+        for (idx, notifier) in self.notifiers.iter().enumerate() {
+            match &notifier.kind {
+                NotifierKind::Webhook { url }
+                | NotifierKind::Discord { url }
+                | NotifierKind::Slack { url }
+                    if !(url.starts_with("http://") || url.starts_with("https://")) =>
+                {
+                    return Err(format!(
+                        "Invalid config: `notifiers[{}].url` must be an http(s) URL",
+                        idx
+                    ));
+                }
+                NotifierKind::File { path } if path.trim().is_empty() => {
+                    return Err(format!(
+                        "Invalid config: `notifiers[{}].path` must not be empty",
+                        idx
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        self.digest.tz()?;
+        self.digest.daily_time()?;
+        self.digest.weekly_time()?;
+
+        if let Some(webhook) = &self.telegram.webhook {
+            if !webhook.url.starts_with("https://") || reqwest::Url::parse(&webhook.url).is_err() {
+                return Err(
+                    "Invalid config: `telegram.webhook.url` must be an https URL".to_string(),
+                );
+            }
+            if webhook.listen.parse::<std::net::SocketAddr>().is_err() {
+                return Err(
+                    "Invalid config: `telegram.webhook.listen` must be an address like 0.0.0.0:8443"
+                        .to_string(),
+                );
+            }
+            if webhook.tls_cert.is_some() != webhook.tls_key.is_some() {
+                return Err(
+                    "Invalid config: `telegram.webhook.tls_cert` and `tls_key` go together"
+                        .to_string(),
+                );
+            }
+        }
+
+        for (idx, user) in self.telegram.users.iter().enumerate() {
+            if self.telegram.users[..idx].iter().any(|u| u.id == user.id) {
+                return Err(format!(
+                    "Invalid config: `telegram.users[{}].id` duplicates user {}",
+                    idx, user.id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StrategyConfig {
+    /// Rejects values that would make the strategy misbehave, naming the bad field
+    pub fn validate(&self) -> Result<(), String> {
+        fn invalid(field: &str, reason: &str) -> Result<(), String> {
+            Err(format!("Invalid config: `{}` {}", field, reason))
+        }
+
+        if let Some(name) = &self.name
+            && (name.trim().is_empty() || name.contains(char::is_whitespace))
+        {
+            return invalid("name", "must be a non-empty word without spaces");
+        }
+        if self.left_asset.trim().is_empty() {
+            return invalid("left_asset", "must not be empty");
+        }
+        if self.right_asset.trim().is_empty() {
+            return invalid("right_asset", "must not be empty");
+        }
+        if self.left_asset == self.right_asset {
+            return invalid("right_asset", "must differ from `left_asset`");
+        }
+        if self.binance_symbol.trim().is_empty() {
+            return invalid("binance_symbol", "must not be empty");
+        }
+        if !(self.sell_percentage > 0.0 && self.sell_percentage <= 100.0) {
+            return invalid("sell_percentage", "must be in (0, 100]");
+        }
+        if !(self.dca_recover_percentage > 0.0 && self.dca_recover_percentage < 100.0) {
+            return invalid("dca_recover_percentage", "must be in (0, 100)");
+        }
+        if !(self.r_factor > 0.0 && self.r_factor <= 1.0) {
+            return invalid("r_factor", "must be in (0, 1]");
+        }
+        if !(self.trail_percentage >= 0.0 && self.trail_percentage < 100.0) {
+            return invalid("trail_percentage", "must be in [0, 100)");
+        }
+        if let Some(pct) = self.stop_loss_percentage
+            && !(pct > 0.0 && pct < 100.0)
+        {
+            return invalid("stop_loss_percentage", "must be in (0, 100)");
+        }
+        if let Some(amount) = self.stop_loss_amount
+            && !(amount > 0.0 && amount.is_finite())
+        {
+            return invalid("stop_loss_amount", "must be a positive number");
+        }
+        if let Some(capital) = self.allocated_capital
+            && !(capital > 0.0 && capital.is_finite())
+        {
+            return invalid("allocated_capital", "must be a positive number");
+        }
+        if self.cooldown_secs < 0 {
+            return invalid("cooldown_secs", "must not be negative");
+        }
+        if self.trade_retries == 0 {
+            return invalid("trade_retries", "must be at least 1");
+        }
+        if self.slippage_bps == 0 {
+            return invalid("slippage_bps", "must be at least 1");
+        }
+        if self.slippage_bps_max < self.slippage_bps {
+            return invalid("slippage_bps_max", "must be >= `slippage_bps`");
+        }
+        if self.slippage_bps_max > 10_000 {
+            return invalid("slippage_bps_max", "must be <= 10000");
+        }
+        if !(self.min_buy_amount >= 0.0 && self.min_buy_amount.is_finite()) {
+            return invalid("min_buy_amount", "must be a positive number");
+        }
+        if !(self.min_dca_amount >= 0.0 && self.min_dca_amount.is_finite()) {
+            return invalid("min_dca_amount", "must be a positive number");
+        }
+        if !(self.reconcile_tolerance >= 0.0 && self.reconcile_tolerance.is_finite()) {
+            return invalid("reconcile_tolerance", "must be a positive number");
+        }
+        if !(self.sol_fee_reserve >= 0.0 && self.sol_fee_reserve.is_finite()) {
+            return invalid("sol_fee_reserve", "must be a positive number");
+        }
+        let is_http = |url: &str| url.starts_with("http://") || url.starts_with("https://");
+        if !is_http(&self.rpc_url) {
+            return invalid("rpc_url", "must be an http(s) URL");
+        }
+        for (idx, endpoint) in self.rpc_endpoints.iter().enumerate() {
+            if !is_http(&endpoint.url) {
+                return invalid(
+                    &format!("rpc_endpoints[{}].url", idx),
+                    "must be an http(s) URL",
+                );
+            }
+            for name in endpoint.headers.keys() {
+                if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+                    return invalid(
+                        &format!("rpc_endpoints[{}].headers", idx),
+                        "contains an invalid header name",
+                    );
+                }
+            }
+        }
+        if self.rpc_health_interval_secs == 0 {
+            return invalid("rpc_health_interval_secs", "must be at least 1");
+        }
+        if self.rpc_max_latency_ms == 0 {
+            return invalid("rpc_max_latency_ms", "must be at least 1");
+        }
+        if self.log_dir.trim().is_empty() {
+            return invalid("log_dir", "must not be empty");
+        }
+        if !(self.paper.starting_balance >= 0.0 && self.paper.starting_balance.is_finite()) {
+            return invalid("paper.starting_balance", "must be a positive number");
+        }
+        if self.paper.fee_bps >= 10_000 {
+            return invalid("paper.fee_bps", "must be < 10000");
+        }
+        if let StrategyKind::Grid {
+            lower,
+            upper,
+            levels,
+            order_size,
+        } = &self.strategy
+        {
+            if !(2..=200).contains(levels) {
+                return invalid("strategy.levels", "must be in [2, 200]");
+            }
+            match (lower, upper) {
+                (Some(lower), Some(upper)) => {
+                    if !(*lower > 0.0 && lower.is_finite()) {
+                        return invalid("strategy.lower", "must be a positive number");
+                    }
+                    if !(upper > lower && upper.is_finite()) {
+                        return invalid("strategy.upper", "must be greater than `strategy.lower`");
+                    }
+                }
+                (None, None) => {}
+                _ => {
+                    return invalid(
+                        "strategy.upper",
+                        "must be set together with `strategy.lower`",
+                    );
+                }
+            }
+            if let Some(order_size) = order_size
+                && !(*order_size > 0.0 && order_size.is_finite())
+            {
+                return invalid("strategy.order_size", "must be a positive number");
+            }
+        }
+        if let Some(ladder) = &self.dca_ladder {
+            match (&ladder.geometric, ladder.levels.is_empty()) {
+                (None, true) => {
+                    return invalid("dca_ladder.levels", "or `dca_ladder.geometric` must be set");
+                }
+                (Some(_), false) => {
+                    return invalid(
+                        "dca_ladder.levels",
+                        "and `dca_ladder.geometric` are mutually exclusive, set only one",
+                    );
+                }
+                (Some(geometric), true) => {
+                    let Some(max_levels) = ladder.max_levels else {
+                        return invalid("dca_ladder.max_levels", "is required with `geometric`");
+                    };
+                    if !(geometric.drop > 0.0 && geometric.drop * (max_levels as f64) < 100.0) {
+                        return invalid(
+                            "dca_ladder.geometric.drop",
+                            "must be positive and keep the deepest level above 0",
+                        );
+                    }
+                    if !(geometric.entry_percentage > 0.0 && geometric.entry_percentage.is_finite())
+                    {
+                        return invalid(
+                            "dca_ladder.geometric.entry_percentage",
+                            "must be a positive number",
+                        );
+                    }
+                    if !(geometric.ratio > 0.0 && geometric.ratio.is_finite()) {
+                        return invalid("dca_ladder.geometric.ratio", "must be a positive number");
+                    }
+                }
+                (None, false) => {
+                    let mut last_drop = 0.0;
+                    for (idx, level) in ladder.levels.iter().enumerate() {
+                        let field = |name: &str| format!("dca_ladder.levels[{}].{}", idx, name);
+                        if !(level.drop > last_drop && level.drop < 100.0) {
+                            return invalid(
+                                &field("drop"),
+                                "must be in (0, 100) and deeper than the level before",
+                            );
+                        }
+                        last_drop = level.drop;
+                        match (level.amount, level.entry_percentage) {
+                            (Some(size), None) | (None, Some(size))
+                                if size > 0.0 && size.is_finite() => {}
+                            (Some(_), None) => {
+                                return invalid(&field("amount"), "must be a positive number");
+                            }
+                            (None, Some(_)) => {
+                                return invalid(
+                                    &field("entry_percentage"),
+                                    "must be a positive number",
+                                );
+                            }
+                            _ => {
+                                return invalid(
+                                    &field("amount"),
+                                    "or `entry_percentage` must be set (not both)",
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            if ladder.max_levels == Some(0) {
+                return invalid("dca_ladder.max_levels", "must be at least 1");
+            }
+            if ladder.min_spacing_secs < 0 {
+                return invalid("dca_ladder.min_spacing_secs", "must not be negative");
+            }
+        }
+        match &self.venue {
+            VenueConfig::Jupiter => {}
+            VenueConfig::Osmosis {
+                pool_id, address, ..
+            } => {
+                if pool_id.parse::<u64>().is_err() {
+                    return invalid("venue.pool_id", "must be a numeric pool id");
+                }
+                if !address.starts_with("osmo1") {
+                    return invalid("venue.address", "must be an osmo1 address");
+                }
+            }
+            VenueConfig::Mock {
+                prices,
+                balances,
+                fee_bps,
+            } => {
+                if prices.values().any(|p| !(*p > 0.0 && p.is_finite())) {
+                    return invalid("venue.prices", "must all be positive numbers");
+                }
+                if balances.values().any(|b| !(*b >= 0.0 && b.is_finite())) {
+                    return invalid("venue.balances", "must not be negative");
+                }
+                if *fee_bps >= 10_000 {
+                    return invalid("venue.fee_bps", "must be < 10000");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes one of the `TUNABLE_PARAMS` (call `validate` afterwards)
+    pub fn set_param(&mut self, name: &str, value: f64) -> Result<(), String> {
+        let whole = |value: f64| {
+            if value.fract() == 0.0 && value >= 0.0 && value.is_finite() {
+                Ok(value)
+            } else {
+                Err(format!("`{}` must be a whole number", name))
+            }
+        };
+        match name {
+            "sell_percentage" => self.sell_percentage = value,
+            "dca_recover_percentage" => self.dca_recover_percentage = value,
+            "r_factor" => self.r_factor = value,
+            "trail_percentage" => self.trail_percentage = value,
+            "cooldown_secs" => self.cooldown_secs = whole(value)? as i64,
+            "min_buy_amount" => self.min_buy_amount = value,
+            "min_dca_amount" => self.min_dca_amount = value,
+            "slippage_bps" => self.slippage_bps = whole(value)? as u64,
+            "slippage_bps_max" => self.slippage_bps_max = whole(value)? as u64,
+            _ => return Err(format!("`{}` cannot be changed at runtime", name)),
+        }
+        Ok(())
+    }
+
+    /// Current value of one of the `TUNABLE_PARAMS`
+    pub fn get_param(&self, name: &str) -> Option<f64> {
+        match name {
+            "sell_percentage" => Some(self.sell_percentage),
+            "dca_recover_percentage" => Some(self.dca_recover_percentage),
+            "r_factor" => Some(self.r_factor),
+            "trail_percentage" => Some(self.trail_percentage),
+            "cooldown_secs" => Some(self.cooldown_secs as f64),
+            "min_buy_amount" => Some(self.min_buy_amount),
+            "min_dca_amount" => Some(self.min_dca_amount),
+            "slippage_bps" => Some(self.slippage_bps as f64),
+            "slippage_bps_max" => Some(self.slippage_bps_max as f64),
+            _ => None,
+        }
+    }
+
+    /// Label used to address the pair from Telegram (e.g. `SOL/USDC`)
+    pub fn pair_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => {
+                let short = |mint: &str| mint.chars().take(4).collect::<String>();
+                format!("{}/{}", short(&self.left_asset), short(&self.right_asset))
+            }
+        }
+    }
+
+    /// RPC endpoints of the pair, falling back to `rpc_url` alone
+    pub fn rpc_endpoints(&self) -> Vec<RpcEndpointConfig> {
+        if self.rpc_endpoints.is_empty() {
+            vec![RpcEndpointConfig {
+                url: self.rpc_url.clone(),
+                priority: 0,
+                headers: HashMap::new(),
+            }]
+        } else {
+            self.rpc_endpoints.clone()
+        }
+    }
+
+    pub fn is_paper(&self) -> bool {
+        self.mode == TradingMode::Paper
+    }
+
+    /// Directory of the pair state: paper trading lives in its own `paper/` namespace
+    pub fn state_dir(&self) -> String {
+        if self.is_paper() {
+            format!("{}/paper", self.log_dir)
+        } else {
+            self.log_dir.clone()
+        }
+    }
+
+    /// Builds the path of a per-pair log file, e.g. `logs/solana/pair_<left>_<right>_state.json`
+    pub fn pair_log_path(&self, suffix: &str) -> String {
+        format!(
+            "{}/pair_{}_{}_{}",
+            self.state_dir(),
+            self.left_asset,
+            self.right_asset,
+            suffix
+        )
+    }
+
+    /// Path of the Binance price history used by the market risk check
+    pub fn binance_price_log_path(&self) -> String {
+        format!(
+            "{}/binance_{}_{}__prices.csv",
+            self.log_dir, self.left_asset, self.right_asset
+        )
+    }
+}
+
+/// A SOL/USDC pair for unit tests; its temp dir is removed once the test drops it
+#[cfg(test)]
+pub struct TestConfig(StrategyConfig);
+
+#[cfg(test)]
+impl std::ops::Deref for TestConfig {
+    type Target = StrategyConfig;
+
+    fn deref(&self) -> &StrategyConfig {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl std::ops::DerefMut for TestConfig {
+    fn deref_mut(&mut self) -> &mut StrategyConfig {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestConfig {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0.log_dir);
+    }
+}
+
+/// A SOL/USDC pair for unit tests with `extra` settings appended, logging to a fresh
+/// temp dir named after `test`
+#[cfg(test)]
+pub fn test_config(test: &str, extra: &str) -> TestConfig {
+    let log_dir = std::env::temp_dir().join(format!("tradeRS-bot-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&log_dir);
+    std::fs::create_dir_all(&log_dir).unwrap();
+    let config: StrategyConfig = toml::from_str(&format!(
+        r#"
+left_asset = "So11111111111111111111111111111111111111112"
+right_asset = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+sell_percentage = 2.0
+dca_recover_percentage = 3.0
+r_factor = 0.5
+log_dir = "{}"
+{}"#,
+        log_dir.display(),
+        extra
+    ))
+    .unwrap();
+    config.validate().unwrap();
+    TestConfig(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const USDT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+    const PAIR: &str = r#"
+left_asset = "So11111111111111111111111111111111111111112"
+right_asset = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+sell_percentage = 2.0
+dca_recover_percentage = 3.0
+r_factor = 0.5
+"#;
+
+    fn load(test: &str, contents: &str) -> Result<BotConfig, String> {
+        let path =
+            std::env::temp_dir().join(format!("tradeRS-bot-{}-{}.toml", test, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        let config = BotConfig::from_file(&path.display().to_string());
+        let _ = fs::remove_file(&path);
+        config
+    }
+
+    #[test]
+    fn a_single_pair_can_sit_at_the_top_level() {
+        let config = load(
+            "config-single",
+            &format!("{}\n[digest]\ndaily = \"08:00\"", PAIR),
+        )
+        .unwrap();
+        assert_eq!(config.pairs.len(), 1);
+        assert_eq!(config.pairs[0].cooldown_secs, default_cooldown_secs());
+        assert!(config.digest.daily_time().unwrap().is_some());
+    }
+
+    #[test]
+    fn errors_name_the_bad_field_of_the_bad_pair() {
+        let valid = load(
+            "config-pairs",
+            &format!(
+                "[[pairs]]\nname = \"one\"\n{}\n[[pairs]]\nname = \"two\"\n{}",
+                PAIR,
+                PAIR.replace(USDC, USDT)
+            ),
+        )
+        .unwrap();
+        let invalid = |change: fn(&mut BotConfig)| {
+            let mut config = valid.clone();
+            change(&mut config);
+            config.validate().unwrap_err()
+        };
+
+        assert_eq!(
+            invalid(|c| c.pairs[1].sell_percentage = 0.0),
+            "Invalid config: `pairs[1].sell_percentage` must be in (0, 100]"
+        );
+        assert_eq!(
+            invalid(|c| c.pairs[0].slippage_bps_max = c.pairs[0].slippage_bps - 1),
+            "Invalid config: `pairs[0].slippage_bps_max` must be >= `slippage_bps`"
+        );
+        assert_eq!(
+            invalid(|c| {
+                c.risk.max_drawdown_percentage = Some(20.0);
+                c.pairs[0].allocated_capital = Some(500.0);
+            }),
+            "Invalid config: `pairs[1].allocated_capital` is required with `risk.max_drawdown_percentage`"
+        );
+        assert_eq!(
+            invalid(|c| c.pairs[1].name = Some("one".to_string())),
+            "Invalid config: `pairs[1].name` duplicates pair one"
+        );
+        assert_eq!(
+            invalid(|c| c.pairs[1].right_asset = USDC.to_string()),
+            "Invalid config: `pairs[1].right_asset` duplicates the assets of pair one"
+        );
+        assert_eq!(
+            invalid(|c| {
+                c.pairs[0].dca_ladder = Some(DcaLadderConfig {
+                    levels: vec![LadderLevelConfig {
+                        drop: 5.0,
+                        amount: Some(10.0),
+                        entry_percentage: None,
+                    }],
+                    geometric: Some(GeometricLadderConfig {
+                        drop: 5.0,
+                        entry_percentage: 50.0,
+                        ratio: 1.5,
+                    }),
+                    max_levels: Some(2),
+                    min_spacing_secs: 0,
+                })
+            }),
+            "Invalid config: `pairs[0].dca_ladder.levels` and `dca_ladder.geometric` are mutually exclusive, set only one"
+        );
+        assert_eq!(
+            invalid(|c| c.pairs.clear()),
+            "Invalid config: `pairs` must contain at least one pair"
+        );
+        assert_eq!(
+            invalid(|c| c.digest.daily = Some("25:00".to_string())),
+            "Invalid config: `digest.daily` must be \"HH:MM\""
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let error = load(
+            "config-unknown",
+            &format!("{}\nsell_precentage = 2.0", PAIR),
+        )
+        .unwrap_err();
+        assert!(error.contains("sell_precentage"), "{error}");
+    }
+}
@@ -0,0 +1,505 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::{StrategyConfig, StrategyKind};
+use crate::error::BotResult;
+use crate::state_store::PairState;
+use crate::strategy::{BuyOrder, DataNeeds, Intent, MarketSnapshot, SellOrder, Strategy};
+use crate::trade_stats::trade_time;
+
+/// DCA levels planned from `[pairs.dca_ladder]` when the position opened, saved with it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcaLadder {
+    pub min_spacing_secs: i64,
+    pub steps: Vec<LadderStep>, // DCA level n buys at `steps[n - 1]`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LadderStep {
+    pub trigger_price: f64, // Exit price (`right_asset` per `left_asset`) at or below which it buys
+    pub spend: f64,         // `right_asset` to spend
+}
+
+/// The ladder of a DCA position opened by spending `spent` for `received`
+pub fn plan_ladder(config: &StrategyConfig, spent: f64, received: f64) -> Option<DcaLadder> {
+    let ladder = config.dca_ladder.as_ref()?;
+    if !matches!(config.strategy, StrategyKind::Dca) || received <= 0.0 {
+        return None;
+    }
+    let entry = spent / received;
+    let step = |drop: f64, spend: f64| LadderStep {
+        trigger_price: entry * (1.0 - drop / 100.0),
+        spend,
+    };
+    let mut steps: Vec<LadderStep> = match &ladder.geometric {
+        Some(geometric) => (0..ladder.max_levels.unwrap_or_default())
+            .map(|idx| {
+                step(
+                    geometric.drop * (idx + 1) as f64,
+                    spent * geometric.entry_percentage / 100.0 * geometric.ratio.powi(idx as i32),
+                )
+            })
+            .collect(),
+        None => ladder
+            .levels
+            .iter()
+            .map(|level| {
+                let spend = level
+                    .amount
+                    .or(level.entry_percentage.map(|pct| spent * pct / 100.0))
+                    .unwrap_or_default();
+                step(level.drop, spend)
+            })
+            .collect(),
+    };
+    if let Some(max_levels) = ladder.max_levels {
+        steps.truncate(max_levels as usize);
+    }
+    Some(DcaLadder {
+        min_spacing_secs: ladder.min_spacing_secs,
+        steps,
+    })
+}
+
+/// Buys when the risk check allows it, averages down every `dca_recover_percentage`
+/// (or along the planned ladder) and sells the whole position once it is
+/// `sell_percentage` in profit
+pub struct DcaStrategy;
+
+impl DcaStrategy {
+    /// Seconds of cooldown left after the last sell
+    fn cooldown_left(
+        config: &StrategyConfig,
+        position: &PairState,
+        now: DateTime<Utc>,
+    ) -> BotResult<Option<i64>> {
+        let Some(last_trade) = position.trades.last() else {
+            return Ok(None);
+        };
+        if last_trade.trade_type != "sell" {
+            return Ok(None);
+        }
+        let last_time = trade_time(last_trade)?;
+        let elapsed = now.signed_duration_since(last_time).num_seconds();
+        Ok((elapsed < config.cooldown_secs).then(|| config.cooldown_secs - elapsed))
+    }
+
+    fn decide_entry(
+        &self,
+        config: &StrategyConfig,
+        market: &MarketSnapshot,
+        position: &PairState,
+    ) -> BotResult<Intent> {
+        if let Some(left) = Self::cooldown_left(config, position, market.now)? {
+            println!("⏳ Cooldown active ({}s left). Skipping buy...", left);
+            return Ok(Intent::Hold(Duration::from_secs(10)));
+        }
+        let Some(risk) = &market.risk else {
+            println!("⚠️ No price history for the risk check. Skipping buy...");
+            return Ok(Intent::Hold(Duration::from_secs(10)));
+        };
+
+        // Calculate the adjusted amount to use based on current USDC balance
+        let adjusted_amount = market.quote_balance.unwrap_or(0.0) * risk.multiplier;
+        match risk.label.as_str() {
+            "🔴 HIGH-RISK" => {
+                println!("❌ Skipping trade due to HIGH RISK.");
+                return Ok(Intent::Hold(Duration::from_secs(5)));
+            }
+            "⚠️ WEAK ZONE" => {
+                println!(
+                    "⚠️ Weak zone detected. Reducing trade size to {:.2}.",
+                    adjusted_amount
+                );
+            }
+            _ => {
+                println!(
+                    "✅ Risk acceptable. Using adjusted size: {:.2}",
+                    adjusted_amount
+                );
+            }
+        }
+
+        // Ensure the adjusted amount meets a minimum threshold
+        if adjusted_amount < config.min_buy_amount {
+            println!(
+                "⚠️ Adjusted amount {:.2} too small to execute. Skipping.",
+                adjusted_amount
+            );
+            return Ok(Intent::Hold(Duration::from_secs(10)));
+        }
+
+        println!("🚀 Market & Risk Passed. Preparing to Buy...");
+        Ok(Intent::Buy(BuyOrder {
+            spend: adjusted_amount,
+            dca_level: position.dca_level,
+            grid: None,
+        }))
+    }
+
+    fn decide_exit(
+        &self,
+        config: &StrategyConfig,
+        market: &MarketSnapshot,
+        position: &PairState,
+    ) -> BotResult<Intent> {
+        println!("📈 Checking SELL conditions...");
+        let holding = position.holding;
+        let paid = position.open_cost();
+        println!(
+            "🔁 Holding: {:.6} {} → Avg entry {:.6}",
+            holding,
+            config.left_asset,
+            paid / holding
+        );
+
+        let Some(proceeds) = market.exit_quote else {
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        };
+        println!(
+            "🔁 Would return {:.6} {} for selling {:.6} {}",
+            proceeds, config.right_asset, holding, config.left_asset
+        );
+
+        let target_return = paid * (1.0 + config.sell_percentage / 100.0);
+        println!(
+            "🎯 Need at least {:.6} {} to sell for profit (+{}%)",
+            target_return, config.right_asset, config.sell_percentage
+        );
+        if config.trail_percentage > 0.0 {
+            if proceeds >= target_return || position.trailing_peak.is_some() {
+                return Ok(Self::decide_trailing(
+                    config,
+                    position,
+                    proceeds / holding,
+                    target_return,
+                ));
+            }
+        } else if proceeds >= target_return {
+            println!("✅ SELL opportunity detected!");
+            return Ok(Intent::Sell(SellOrder {
+                amount: holding,
+                grid: None,
+            }));
+        }
+
+        let price_change = 100.0 * (proceeds / paid - 1.0);
+        println!("📉 Price is at {:+.2}%", price_change);
+        if let Some(ladder) = &position.ladder {
+            return Self::decide_ladder(config, market, position, ladder, proceeds / holding);
+        }
+        if price_change > -config.dca_recover_percentage {
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        }
+
+        println!("🛒 DCA Triggered! Buying the dip...");
+        // Each DCA level invests r_factor of the balance (e.g., 0.5 for 50%)
+        let dca_amount = market.quote_balance.unwrap_or(0.0) * config.r_factor;
+        if dca_amount < config.min_dca_amount {
+            println!("⚠️ DCA amount too small ({:.2}). Skipping.", dca_amount);
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        }
+        println!(
+            "🔁 DCA Buy: Investing {:.2} {}",
+            dca_amount, config.right_asset
+        );
+        Ok(Intent::Buy(BuyOrder {
+            spend: dca_amount,
+            dca_level: position.dca_level + 1,
+            grid: None,
+        }))
+    }
+
+    /// Buys the next planned level once the price reaches it and the spacing allows it
+    fn decide_ladder(
+        config: &StrategyConfig,
+        market: &MarketSnapshot,
+        position: &PairState,
+        ladder: &DcaLadder,
+        price: f64,
+    ) -> BotResult<Intent> {
+        let level = position.dca_level + 1;
+        let Some(step) = ladder.steps.get(position.dca_level as usize) else {
+            println!(
+                "🪜 All {} ladder levels used. Holding...",
+                ladder.steps.len()
+            );
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        };
+        if price > step.trigger_price {
+            println!(
+                "🪜 Ladder level {} buys at {:.6} (price {:.6})",
+                level, step.trigger_price, price
+            );
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        }
+        if let Some(last_buy) = position.trades.iter().rev().find(|t| t.trade_type == "buy") {
+            let elapsed = market
+                .now
+                .signed_duration_since(trade_time(last_buy)?)
+                .num_seconds();
+            if elapsed < ladder.min_spacing_secs {
+                println!(
+                    "⏳ Ladder level {} reached, waiting {}s since the last buy",
+                    level,
+                    ladder.min_spacing_secs - elapsed
+                );
+                return Ok(Intent::Hold(Duration::from_secs(10)));
+            }
+        }
+
+        let spend = step.spend.min(market.quote_balance.unwrap_or(0.0));
+        if spend < config.min_dca_amount {
+            println!("⚠️ DCA amount too small ({:.2}). Skipping.", spend);
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        }
+        println!(
+            "🛒 DCA ladder level {}: investing {:.2} {}",
+            level, spend, config.right_asset
+        );
+        Ok(Intent::Buy(BuyOrder {
+            spend,
+            dca_level: level,
+            grid: None,
+        }))
+    }
+
+    /// Past the target: follow the peak exit price and sell once it falls back
+    /// `trail_percentage` from it (or down to the target, whichever is higher)
+    fn decide_trailing(
+        config: &StrategyConfig,
+        position: &PairState,
+        price: f64,
+        target_return: f64,
+    ) -> Intent {
+        let peak = position.trailing_peak.unwrap_or(0.0).max(price);
+        let floor =
+            (peak * (1.0 - config.trail_percentage / 100.0)).max(target_return / position.holding);
+        if position.trailing_peak.is_none_or(|stored| peak > stored) {
+            println!(
+                "🪁 New peak {:.6}, trailing take-profit sells below {:.6}",
+                peak, floor
+            );
+            return Intent::Trail(peak);
+        }
+        if price <= floor {
+            println!(
+                "✅ Trailing take-profit hit: {:.6} is {:.2}% below the peak {:.6}",
+                price,
+                100.0 * (1.0 - price / peak),
+                peak
+            );
+            return Intent::Sell(SellOrder {
+                amount: position.holding,
+                grid: None,
+            });
+        }
+        println!(
+            "🪁 Trailing: price {:.6}, peak {:.6}, sells below {:.6}",
+            price, peak, floor
+        );
+        Intent::Hold(Duration::from_secs(5))
+    }
+}
+
+impl Strategy for DcaStrategy {
+    fn name(&self) -> &'static str {
+        "dca"
+    }
+
+    fn needs(
+        &self,
+        config: &StrategyConfig,
+        position: &PairState,
+        now: DateTime<Utc>,
+    ) -> DataNeeds {
+        if position.holding != 0.0 {
+            return DataNeeds {
+                quote_balance: true,
+                exit_quote: true,
+                ..DataNeeds::default()
+            };
+        }
+        // Nothing to look at while cooling down (a bad trade time is reported by `decide`)
+        if !matches!(Self::cooldown_left(config, position, now), Ok(None)) {
+            return DataNeeds::default();
+        }
+        DataNeeds {
+            risk: true,
+            quote_balance: true,
+            ..DataNeeds::default()
+        }
+    }
+
+    fn decide(
+        &mut self,
+        config: &StrategyConfig,
+        market: &MarketSnapshot,
+        position: &PairState,
+    ) -> BotResult<Intent> {
+        if position.holding == 0.0 {
+            self.decide_entry(config, market, position)
+        } else {
+            self.decide_exit(config, market, position)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::strategy::RiskAssessment;
+    use chrono::Duration as TimeDelta;
+
+    fn very_safe() -> RiskAssessment {
+        RiskAssessment {
+            label: "✅ VERY SAFE".to_string(),
+            multiplier: 0.5,
+        }
+    }
+
+    #[test]
+    fn cooldown_follows_the_latest_sell() {
+        let config = test_config("dca-cooldown", "cooldown_secs = 600");
+        let start = Utc::now() - TimeDelta::hours(2);
+        let mut position = PairState::default();
+        position.record_buy(100.0, 1.0, 0, start);
+        position.record_sell(1.0, 103.0, start + TimeDelta::minutes(10));
+        position.record_buy(100.0, 1.0, 0, start + TimeDelta::minutes(30));
+        let second_sell = Utc::now() - TimeDelta::seconds(60);
+        position.record_sell(1.0, 103.0, second_sell);
+
+        let market = MarketSnapshot {
+            now: Utc::now(),
+            risk: Some(very_safe()),
+            quote_balance: Some(500.0),
+            ..MarketSnapshot::default()
+        };
+        assert_eq!(
+            DcaStrategy.decide(&config, &market, &position).unwrap(),
+            Intent::Hold(Duration::from_secs(10))
+        );
+        assert!(!DcaStrategy.needs(&config, &position, market.now).risk);
+
+        let later = MarketSnapshot {
+            now: second_sell + TimeDelta::seconds(601),
+            ..market
+        };
+        assert!(matches!(
+            DcaStrategy.decide(&config, &later, &position).unwrap(),
+            Intent::Buy(order) if order.spend == 250.0
+        ));
+    }
+
+    fn trigger_prices_and_spends(ladder: &DcaLadder) -> Vec<(f64, f64)> {
+        ladder
+            .steps
+            .iter()
+            .map(|step| {
+                (
+                    (step.trigger_price * 1e6).round() / 1e6,
+                    (step.spend * 1e6).round() / 1e6,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn explicit_levels_are_planned_from_the_opening_buy() {
+        let config = test_config(
+            "ladder-levels",
+            r#"
+[dca_ladder]
+max_levels = 2
+min_spacing_secs = 300
+levels = [
+    { drop = 5.0, amount = 40.0 },
+    { drop = 10.0, entry_percentage = 150.0 },
+    { drop = 20.0, amount = 500.0 },
+]"#,
+        );
+        // Entry price 200 for 100 spent
+        let ladder = plan_ladder(&config, 100.0, 0.5).unwrap();
+        assert_eq!(ladder.min_spacing_secs, 300);
+        assert_eq!(
+            trigger_prices_and_spends(&ladder),
+            vec![(190.0, 40.0), (180.0, 150.0)]
+        );
+        assert_eq!(plan_ladder(&config, 100.0, 0.0), None);
+        assert_eq!(
+            plan_ladder(&test_config("ladder-none", ""), 100.0, 0.5),
+            None
+        );
+    }
+
+    #[test]
+    fn geometric_levels_deepen_and_grow_by_the_ratio() {
+        let config = test_config(
+            "ladder-geometric",
+            r#"
+[dca_ladder]
+max_levels = 3
+geometric = { drop = 4.0, entry_percentage = 50.0, ratio = 2.0 }"#,
+        );
+        let ladder = plan_ladder(&config, 100.0, 1.0).unwrap();
+        assert_eq!(
+            trigger_prices_and_spends(&ladder),
+            vec![(96.0, 50.0), (92.0, 100.0), (88.0, 200.0)]
+        );
+    }
+
+    #[test]
+    fn ladder_buys_the_next_level_once_reached_and_spaced() {
+        let config = test_config(
+            "ladder-decide",
+            "[dca_ladder]\nmin_spacing_secs = 600\nlevels = [{ drop = 5.0, amount = 40.0 }]",
+        );
+        let opened = Utc::now() - TimeDelta::seconds(300);
+        let mut position = PairState::default();
+        position.record_buy(100.0, 1.0, 0, opened);
+        position.ladder = plan_ladder(&config, 100.0, 1.0);
+        let market = |exit_quote: f64, now: DateTime<Utc>| MarketSnapshot {
+            now,
+            exit_quote: Some(exit_quote),
+            quote_balance: Some(500.0),
+            ..MarketSnapshot::default()
+        };
+
+        let hold = Intent::Hold(Duration::from_secs(5));
+        assert_eq!(
+            DcaStrategy
+                .decide(&config, &market(96.0, Utc::now()), &position)
+                .unwrap(),
+            hold
+        );
+        // Reached, but too soon after the opening buy
+        assert_eq!(
+            DcaStrategy
+                .decide(&config, &market(95.0, Utc::now()), &position)
+                .unwrap(),
+            Intent::Hold(Duration::from_secs(10))
+        );
+        let later = opened + TimeDelta::seconds(601);
+        assert_eq!(
+            DcaStrategy
+                .decide(&config, &market(95.0, later), &position)
+                .unwrap(),
+            Intent::Buy(BuyOrder {
+                spend: 40.0,
+                dca_level: 1,
+                grid: None,
+            })
+        );
+
+        position.record_buy(40.0, 0.42, 1, later);
+        let much_later = later + TimeDelta::hours(1);
+        assert_eq!(
+            DcaStrategy
+                .decide(&config, &market(100.0, much_later), &position)
+                .unwrap(),
+            hold
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::config::{DigestConfig, Severity, StrategyConfig};
+use crate::error::{BotError, BotResult};
+use crate::event_log::{EventKind, events_since};
+use crate::notifier::notify;
+use crate::runtime_params::effective_config;
+use crate::state_store::{StateStore, write_atomic};
+use crate::swap_venue::build_venue;
+use crate::token_registry::token_registry;
+use crate::trade_stats::{Cycle, completed_cycles, paper_tag, trade_time};
+
+/// How often the scheduler checks whether a digest is due
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// Risk label changes listed in a digest, newest last
+const MAX_RISK_CHANGES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DigestKind {
+    Daily,
+    Weekly,
+}
+
+impl DigestKind {
+    fn label(&self) -> &'static str {
+        match self {
+            DigestKind::Daily => "Daily",
+            DigestKind::Weekly => "Weekly",
+        }
+    }
+}
+
+/// When each digest was last sent, saved so a restart neither repeats nor skips one
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DigestSchedule {
+    daily: Option<DateTime<Utc>>,
+    weekly: Option<DateTime<Utc>>,
+}
+
+impl DigestSchedule {
+    fn load(path: &str) -> BotResult<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| BotError::StateIo(format!("Corrupted {}: {}", path, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, path: &str) -> BotResult<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| BotError::StateIo(format!("Failed to encode {}: {}", path, e)))?;
+        write_atomic(path, &content)
+    }
+
+    fn last_sent(&mut self, kind: DigestKind) -> &mut Option<DateTime<Utc>> {
+        match kind {
+            DigestKind::Daily => &mut self.daily,
+            DigestKind::Weekly => &mut self.weekly,
+        }
+    }
+}
+
+/// `time` on `date` in `tz`; a time skipped by a DST change moves one hour later
+fn local_to_utc(tz: Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let naive = date.and_time(time);
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| naive.and_utc())
+}
+
+/// The most recent scheduled time at or before `now`
+fn latest_slot(
+    now: DateTime<Utc>,
+    tz: Tz,
+    weekday: Option<Weekday>,
+    time: NaiveTime,
+) -> DateTime<Utc> {
+    let mut date = now.with_timezone(&tz).date_naive();
+    let step = match weekday {
+        Some(weekday) => {
+            let days_back =
+                (date.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+            date -= Duration::days(days_back as i64);
+            Duration::weeks(1)
+        }
+        None => Duration::days(1),
+    };
+    loop {
+        let slot = local_to_utc(tz, date, time);
+        if slot <= now {
+            return slot;
+        }
+        date -= step;
+    }
+}
+
+/// Sends the configured digests on time, catching up on one missed while the bot was down
+pub async fn run_digests(digest: DigestConfig, pairs: Vec<StrategyConfig>) {
+    // Validated with the config
+    let (Ok(tz), Ok(daily), Ok(weekly)) = (digest.tz(), digest.daily_time(), digest.weekly_time())
+    else {
+        return;
+    };
+    let slots: Vec<(DigestKind, Option<Weekday>, NaiveTime)> = daily
+        .map(|time| (DigestKind::Daily, None, time))
+        .into_iter()
+        .chain(weekly.map(|(day, time)| (DigestKind::Weekly, Some(day), time)))
+        .collect();
+    if slots.is_empty() {
+        return;
+    }
+
+    let path = format!("{}/digest_schedule.json", pairs[0].log_dir);
+    let mut schedule = match DigestSchedule::load(&path) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            eprintln!("⚠️ {}, starting a new digest schedule", e);
+            DigestSchedule::default()
+        }
+    };
+    println!("🗓️ Digests scheduled ({})", tz);
+
+    loop {
+        let now = Utc::now();
+        for (kind, weekday, time) in &slots {
+            let slot = latest_slot(now, tz, *weekday, *time);
+            let last_sent = schedule.last_sent(*kind);
+            match *last_sent {
+                Some(last) if last >= slot => continue,
+                Some(last) => {
+                    let message = digest_message(*kind, &pairs, last, now, tz).await;
+                    notify(Severity::Info, &message).await;
+                }
+                // First run: nothing to summarize yet, the next digest counts from now
+                None => {}
+            }
+            *last_sent = Some(now);
+            if let Err(e) = schedule.save(&path) {
+                eprintln!("⚠️ Failed to save the digest schedule: {}", e);
+            }
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+async fn digest_message(
+    kind: DigestKind,
+    pairs: &[StrategyConfig],
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> String {
+    let mut sections = vec![format!(
+        "🗓️ *{} digest* ({} → {} {})",
+        kind.label(),
+        since.with_timezone(&tz).format("%a %d %b %H:%M"),
+        now.with_timezone(&tz).format("%a %d %b %H:%M"),
+        tz
+    )];
+    for config in pairs {
+        sections.push(match pair_digest(config, since, tz).await {
+            Ok(section) => section,
+            Err(e) => format!("📊 *{}*\n⚠️ Summary unavailable: {}", config.pair_name(), e),
+        });
+    }
+    sections.join("\n\n")
+}
+
+/// Trades, PnL, position and wallet from the trade log and the venue, the rest from the event record
+async fn pair_digest(config: &StrategyConfig, since: DateTime<Utc>, tz: Tz) -> BotResult<String> {
+    let config = effective_config(config)?;
+    let state = StateStore::open(&config)?.load()?;
+    let left_symbol = token_registry().symbol(&config.left_asset).await;
+    let right_symbol = token_registry().symbol(&config.right_asset).await;
+    let mut lines = vec![format!("📊 *{}*{}", config.pair_name(), paper_tag(&config))];
+
+    let trades: Vec<_> = state
+        .trades
+        .iter()
+        .filter(|trade| trade_time(trade).is_ok_and(|time| time >= since))
+        .collect();
+    let count = |kind: &str| trades.iter().filter(|t| t.trade_type == kind).count();
+    lines.push(if trades.is_empty() {
+        "🧾 No trades".to_string()
+    } else {
+        format!(
+            "🧾 Trades: {} ({} buys, {} sells, {} partial sells)",
+            trades.len(),
+            count("buy"),
+            count("sell"),
+            count("partial_sell")
+        )
+    });
+
+    let cycles: Vec<Cycle> = completed_cycles(&state.trades)?
+        .into_iter()
+        .filter(|cycle| cycle.closed >= since)
+        .collect();
+    lines.push(if cycles.is_empty() {
+        "💰 No completed cycles".to_string()
+    } else {
+        format!(
+            "💰 Realized: {:+.2} {} over {} cycles",
+            cycles.iter().map(Cycle::profit).sum::<f64>(),
+            right_symbol,
+            cycles.len()
+        )
+    });
+
+    lines.push(if state.holding > 0.0 {
+        format!(
+            "📦 Position: {:.6} {} for {:.2} {} (DCA level {})",
+            state.holding,
+            left_symbol,
+            state.open_cost(),
+            right_symbol,
+            state.dca_level
+        )
+    } else {
+        "📦 No open position".to_string()
+    });
+
+    // A venue that cannot be built only costs the wallet line, the rest comes from state
+    let balances = match build_venue(&config) {
+        Ok(venue) => match (
+            venue.balance(&config.left_asset).await,
+            venue.balance(&config.right_asset).await,
+        ) {
+            (Ok(left), Ok(right)) => format!(
+                "👛 Wallet: {:.6} {}, {:.2} {}",
+                left, left_symbol, right, right_symbol
+            ),
+            (Err(e), _) | (_, Err(e)) => format!("👛 Wallet: unavailable ({})", e),
+        },
+        Err(e) => format!("👛 Wallet: unavailable ({})", e),
+    };
+    lines.push(balances);
+
+    let events = events_since(&config.pair_name(), since);
+    let risks: Vec<_> = events
+        .iter()
+        .filter(|e| e.kind == EventKind::Risk)
+        .collect();
+    if risks.is_empty() {
+        lines.push("🚦 No risk checks".to_string());
+    } else {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        let mut changes = Vec::new();
+        for (idx, event) in risks.iter().enumerate() {
+            match counts.iter_mut().find(|(label, _)| *label == event.detail) {
+                Some((_, count)) => *count += event.count,
+                None => counts.push((&event.detail, event.count)),
+            }
+            if idx == 0 || risks[idx - 1].detail != event.detail {
+                changes.push(format!(
+                    "{} {}",
+                    event.time.with_timezone(&tz).format("%a %H:%M"),
+                    event.detail
+                ));
+            }
+        }
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(label, count)| format!("{} ×{}", label, count))
+            .collect();
+        lines.push(format!("🚦 Risk: {}", counts.join(", ")));
+        let skipped = changes.len().saturating_sub(MAX_RISK_CHANGES);
+        lines.push(format!("↪️ {}", changes[skipped..].join(" → ")));
+    }
+
+    let errors: Vec<_> = events
+        .iter()
+        .filter(|e| e.kind == EventKind::Error)
+        .collect();
+    let retries: usize = events
+        .iter()
+        .filter(|e| e.kind == EventKind::Retry)
+        .map(|e| e.count)
+        .sum();
+    lines.push(format!(
+        "⚠️ Errors: {}, retries: {}",
+        errors.iter().map(|e| e.count).sum::<usize>(),
+        retries
+    ));
+    if let Some(last) = errors.last() {
+        lines.push(format!(
+            "Last error ({}): {}",
+            last.last_time.with_timezone(&tz).format("%a %H:%M"),
+            last.detail
+        ));
+    }
+    Ok(lines.join("\n"))
+}
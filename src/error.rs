@@ -0,0 +1,176 @@
+use solana_client::client_error::ClientError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::rpc_pool::RpcPoolError;
+
+/// Jupiter program error raised when the fill is worse than the slippage tolerance
+const JUPITER_SLIPPAGE_EXCEEDED: u32 = 6001;
+/// SPL token program error for a transfer larger than the account balance
+const SPL_TOKEN_INSUFFICIENT_FUNDS: u32 = 1;
+
+/// Reason the whole bot was halted, if any
+static HALT_REASON: Mutex<Option<String>> = Mutex::new(None);
+
+pub type BotResult<T> = Result<T, BotError>;
+
+/// Every failure the trading loop can run into
+#[derive(Debug)]
+pub enum BotError {
+    Network(String),           // RPC, HTTP API or Telegram unreachable / timed out / rate limited
+    VenueRejected(String),     // The venue refused the request (no route, failed transaction, ...)
+    Slippage(String),          // The price moved beyond the slippage tolerance
+    InsufficientFunds(String), // Not enough tokens (or SOL for fees) in the wallet
+    StateIo(String),           // Reading or writing the pair state files failed
+    Config(String),            // Missing or invalid settings (env vars, wallet, venue)
+    Unconfirmed(String),       // A swap was sent but whether it landed is unknown
+}
+
+/// What the trading loop does about an error
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorAction {
+    Retry,         // Try the same operation again (raising slippage for swaps)
+    SkipIteration, // Give up for now and look at the market again on the next iteration
+    PausePair,     // Stop trading this pair until /start_trading
+    Halt,          // Stop every pair and alert: the state may need a manual check
+}
+
+impl BotError {
+    pub fn action(&self) -> ErrorAction {
+        match self {
+            BotError::Network(_) | BotError::Slippage(_) => ErrorAction::Retry,
+            BotError::VenueRejected(_) => ErrorAction::SkipIteration,
+            BotError::InsufficientFunds(_) | BotError::Config(_) => ErrorAction::PausePair,
+            // Retrying an unconfirmed swap could fill it twice
+            BotError::StateIo(_) | BotError::Unconfirmed(_) => ErrorAction::Halt,
+        }
+    }
+
+    /// Best guess from the message, for errors that only carry text
+    pub fn classify(message: &str) -> BotError {
+        let lower = message.to_lowercase();
+        let message = message.to_string();
+        if lower.contains("slippage") || lower.contains("0x1771") {
+            BotError::Slippage(message)
+        } else if lower.contains("insufficient") {
+            BotError::InsufficientFunds(message)
+        } else if lower.contains("timed out")
+            || lower.contains("timeout")
+            || lower.contains("connection")
+            || lower.contains("429")
+            || lower.contains("too many requests")
+            || lower.contains("blockhash not found")
+        {
+            BotError::Network(message)
+        } else {
+            BotError::VenueRejected(message)
+        }
+    }
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotError::Network(e) => write!(f, "Network error: {}", e),
+            BotError::VenueRejected(e) => write!(f, "Venue rejected: {}", e),
+            BotError::Slippage(e) => write!(f, "Slippage exceeded: {}", e),
+            BotError::InsufficientFunds(e) => write!(f, "Insufficient funds: {}", e),
+            BotError::StateIo(e) => write!(f, "State I/O error: {}", e),
+            BotError::Config(e) => write!(f, "Config error: {}", e),
+            BotError::Unconfirmed(e) => write!(f, "Unconfirmed swap: {}", e),
+        }
+    }
+}
+
+impl StdError for BotError {}
+
+impl From<std::io::Error> for BotError {
+    fn from(e: std::io::Error) -> Self {
+        BotError::StateIo(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for BotError {
+    fn from(e: serde_json::Error) -> Self {
+        BotError::StateIo(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for BotError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) if status.is_client_error() && status.as_u16() != 429 => {
+                BotError::VenueRejected(e.to_string())
+            }
+            _ => BotError::Network(e.to_string()),
+        }
+    }
+}
+
+impl From<RpcPoolError> for BotError {
+    fn from(e: RpcPoolError) -> Self {
+        match e {
+            RpcPoolError::Unavailable(_) => BotError::Network(e.to_string()),
+            RpcPoolError::Rpc { .. } => BotError::classify(&e.to_string()),
+        }
+    }
+}
+
+impl From<ClientError> for BotError {
+    fn from(e: ClientError) -> Self {
+        let message = e.to_string();
+        match e.get_transaction_error() {
+            Some(TransactionError::InstructionError(
+                _,
+                InstructionError::Custom(JUPITER_SLIPPAGE_EXCEEDED),
+            )) => BotError::Slippage(message),
+            Some(TransactionError::InstructionError(
+                _,
+                InstructionError::Custom(SPL_TOKEN_INSUFFICIENT_FUNDS),
+            ))
+            | Some(TransactionError::InsufficientFundsForFee)
+            | Some(TransactionError::InsufficientFundsForRent { .. }) => {
+                BotError::InsufficientFunds(message)
+            }
+            Some(_) => BotError::VenueRejected(message),
+            None => BotError::classify(&message),
+        }
+    }
+}
+
+/// Errors from the HTTP / RPC helpers in `utils`
+impl From<Box<dyn StdError + Send + Sync>> for BotError {
+    fn from(e: Box<dyn StdError + Send + Sync>) -> Self {
+        let e = match e.downcast::<BotError>() {
+            Ok(e) => return *e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<ClientError>() {
+            Ok(e) => return (*e).into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<RpcPoolError>() {
+            Ok(e) => return (*e).into(),
+            Err(e) => e,
+        };
+        match e.downcast::<reqwest::Error>() {
+            Ok(e) => (*e).into(),
+            Err(e) => BotError::classify(&e.to_string()),
+        }
+    }
+}
+
+/// Stops every pair until the process is restarted
+pub fn halt(reason: &str) {
+    let mut halt_reason = HALT_REASON.lock().unwrap();
+    if halt_reason.is_none() {
+        *halt_reason = Some(reason.to_string());
+    }
+}
+
+pub fn halt_reason() -> Option<String> {
+    HALT_REASON.lock().unwrap().clone()
+}
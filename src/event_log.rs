@@ -0,0 +1,139 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Runs older than this are dropped, the weekly digest plus a day of slack
+const MAX_AGE_DAYS: i64 = 8;
+/// Backstop when labels flip on every check, each run only holds one label
+const MAX_RUNS: usize = 20_000;
+
+/// What happened during this run that the trade log does not show, kept in memory only
+static EVENTS: Mutex<VecDeque<Event>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Error, // Any error handled by the strategy loop
+    Retry, // A failed swap attempt that is tried again
+    Risk,  // A `PriceTouchAnalyzer` label, e.g. "🟢 SAFE"
+}
+
+/// Consecutive events of a pair with the same kind and detail, e.g. the same risk label on
+/// every check, are kept as one run
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub time: DateTime<Utc>,      // First event of the run
+    pub last_time: DateTime<Utc>, // Latest event of the run
+    pub count: usize,
+    pub pair: String,
+    pub kind: EventKind,
+    pub detail: String,
+}
+
+pub fn record_event(pair: &str, kind: EventKind, detail: &str) {
+    let mut events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    push_event(&mut events, Utc::now(), pair, kind, detail);
+}
+
+fn push_event(
+    events: &mut VecDeque<Event>,
+    now: DateTime<Utc>,
+    pair: &str,
+    kind: EventKind,
+    detail: &str,
+) {
+    let oldest = now - Duration::days(MAX_AGE_DAYS);
+    while events
+        .front()
+        .is_some_and(|event| event.last_time < oldest || events.len() >= MAX_RUNS)
+    {
+        events.pop_front();
+    }
+
+    if let Some(run) = events
+        .iter_mut()
+        .rev()
+        .find(|event| event.pair == pair && event.kind == kind)
+        && run.detail == detail
+    {
+        run.last_time = now;
+        run.count += 1;
+        return;
+    }
+    events.push_back(Event {
+        time: now,
+        last_time: now,
+        count: 1,
+        pair: pair.to_string(),
+        kind,
+        detail: detail.to_string(),
+    });
+}
+
+/// Runs of a pair still going at or after `since`, oldest first. A run that started before
+/// `since` is returned whole, so its count includes the events before `since`.
+pub fn events_since(pair: &str, since: DateTime<Utc>) -> Vec<Event> {
+    let events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    events
+        .iter()
+        .filter(|event| event.pair == pair && event.last_time >= since)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_events_are_counted_in_one_run() {
+        let mut events = VecDeque::new();
+        let start = Utc::now();
+        let at = |secs| start + Duration::seconds(secs);
+
+        for secs in 0..1000 {
+            push_event(
+                &mut events,
+                at(secs),
+                "SOL/USDC",
+                EventKind::Risk,
+                "🟢 SAFE",
+            );
+        }
+        push_event(
+            &mut events,
+            at(1000),
+            "SOL/USDC",
+            EventKind::Retry,
+            "timeout",
+        );
+        push_event(
+            &mut events,
+            at(1001),
+            "SOL/USDC",
+            EventKind::Risk,
+            "🟢 SAFE",
+        );
+        push_event(
+            &mut events,
+            at(1002),
+            "SOL/USDC",
+            EventKind::Risk,
+            "🔴 RISK",
+        );
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].count, 1001);
+        assert_eq!(events[0].last_time, at(1001));
+        assert_eq!(events[2].detail, "🔴 RISK");
+
+        // A week later the old runs are gone
+        push_event(
+            &mut events,
+            at(9 * 86_400),
+            "SOL/USDC",
+            EventKind::Error,
+            "rpc",
+        );
+        assert_eq!(events.len(), 1);
+    }
+}
@@ -0,0 +1,396 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::StrategyConfig;
+use crate::error::BotResult;
+use crate::log_manager::Trade;
+use crate::state_store::PairState;
+use crate::strategy::{BuyOrder, DataNeeds, Intent, MarketSnapshot, SellOrder, Strategy};
+
+/// The two levels of a grid lot: bought at `buy`, sold one step up at `sell`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GridLot {
+    pub buy: f64,
+    pub sell: f64,
+}
+
+/// A grid lot bought and not sold yet
+#[derive(Debug, Clone, Copy)]
+pub struct OpenLot {
+    pub lot: GridLot,
+    pub amount: f64, // `left_asset` received
+    pub cost: f64,   // `right_asset` paid
+}
+
+/// Fills and realized profit of the lots bought at one grid level
+#[derive(Debug, Clone, Copy)]
+pub struct LevelFills {
+    pub lot: GridLot,
+    pub buys: usize,
+    pub sells: usize,
+    pub profit: f64, // `right_asset` earned by the sold lots
+}
+
+/// Replays the grid fills of the trade log into per-level fills and the lots still open.
+/// A full exit outside the grid (e.g. /panic_sell) closes every open lot.
+pub fn replay_grid(trades: &[Trade]) -> (Vec<LevelFills>, Vec<OpenLot>) {
+    let mut levels: Vec<LevelFills> = Vec::new();
+    let mut lots: Vec<OpenLot> = Vec::new();
+    for trade in trades {
+        match (trade.trade_type.as_str(), trade.grid) {
+            ("buy", Some(lot)) => {
+                level_fills(&mut levels, lot).buys += 1;
+                lots.push(OpenLot {
+                    lot,
+                    amount: trade.amount_token_b,
+                    cost: trade.amount_token_a,
+                });
+            }
+            ("sell" | "partial_sell", Some(lot)) => {
+                let level = level_fills(&mut levels, lot);
+                level.sells += 1;
+                if let Some(open) = lots.iter().position(|open| open.lot == lot) {
+                    level.profit += trade.amount_token_b - lots.remove(open).cost;
+                }
+            }
+            ("sell", None) => lots.clear(),
+            ("adjust", None) if trade.amount_token_b == 0.0 => lots.clear(),
+            _ => {}
+        }
+    }
+    (levels, lots)
+}
+
+fn level_fills(levels: &mut Vec<LevelFills>, lot: GridLot) -> &mut LevelFills {
+    let idx = match levels.iter().position(|level| level.lot.buy == lot.buy) {
+        Some(idx) => idx,
+        None => {
+            levels.push(LevelFills {
+                lot,
+                buys: 0,
+                sells: 0,
+                profit: 0.0,
+            });
+            levels.len() - 1
+        }
+    };
+    &mut levels[idx]
+}
+
+/// Lots bought by the grid and not sold yet
+pub fn open_lots(trades: &[Trade]) -> Vec<OpenLot> {
+    replay_grid(trades).1
+}
+
+/// Evenly spaced levels from `lower` up; the top level is only a sell target
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GridLayout {
+    lower: f64,
+    step: f64,
+    levels: u32,
+}
+
+impl GridLayout {
+    fn new(lower: f64, upper: f64, levels: u32) -> Self {
+        Self {
+            lower,
+            step: (upper - lower) / (levels - 1) as f64,
+            levels,
+        }
+    }
+
+    fn upper(&self) -> f64 {
+        self.lower + (self.levels - 1) as f64 * self.step
+    }
+
+    /// The level a buy at `price` belongs to: the nearest one at or above it
+    fn buy_level(&self, price: f64) -> Option<f64> {
+        if price < self.lower {
+            return None;
+        }
+        let idx = ((price - self.lower) / self.step).ceil() as u32;
+        (idx < self.levels - 1).then_some(self.lower + idx as f64 * self.step)
+    }
+
+    /// Spans the `levels` most-touched price buckets, widened around their middle
+    /// so a step is at least `min_step_pct`
+    fn from_touches(touches: &[(f64, usize)], levels: u32, min_step_pct: f64) -> Option<Self> {
+        let mut touches = touches.to_vec();
+        touches.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.total_cmp(&b.0)));
+        touches.truncate(levels as usize);
+        if touches.len() < 2 {
+            return None;
+        }
+        let lower = touches.iter().map(|t| t.0).fold(f64::MAX, f64::min);
+        let upper = touches.iter().map(|t| t.0).fold(f64::MIN, f64::max);
+        let middle = (lower + upper) / 2.0;
+        let min_width = middle * min_step_pct / 100.0 * (levels - 1) as f64;
+        let (lower, upper) = if upper - lower < min_width {
+            (middle - min_width / 2.0, middle + min_width / 2.0)
+        } else {
+            (lower, upper)
+        };
+        (lower > 0.0).then(|| Self::new(lower, upper, levels))
+    }
+}
+
+/// Buys a lot at each level the price comes down to and sells it one level up,
+/// so it earns on every swing of a ranging market
+pub struct GridStrategy {
+    levels: u32,
+    order_size: Option<f64>,
+    layout: Option<GridLayout>, // From the config; otherwise derived once and saved in the pair state
+}
+
+impl GridStrategy {
+    pub fn new(
+        lower: Option<f64>,
+        upper: Option<f64>,
+        levels: u32,
+        order_size: Option<f64>,
+    ) -> Self {
+        Self {
+            levels,
+            order_size,
+            layout: lower
+                .zip(upper)
+                .map(|(lower, upper)| GridLayout::new(lower, upper, levels)),
+        }
+    }
+}
+
+impl Strategy for GridStrategy {
+    fn name(&self) -> &'static str {
+        "grid"
+    }
+
+    fn needs(
+        &self,
+        _config: &StrategyConfig,
+        _position: &PairState,
+        _now: DateTime<Utc>,
+    ) -> DataNeeds {
+        DataNeeds {
+            price_history: true,
+            quote_balance: true,
+            ..DataNeeds::default()
+        }
+    }
+
+    fn decide(
+        &mut self,
+        config: &StrategyConfig,
+        market: &MarketSnapshot,
+        position: &PairState,
+    ) -> BotResult<Intent> {
+        let Some(price) = market.price else {
+            println!("⚠️ No Binance price yet. Waiting...");
+            return Ok(Intent::Hold(Duration::from_secs(10)));
+        };
+        // A saved layout with another level count no longer matches the config
+        let saved = position.grid_layout.filter(|l| l.levels == self.levels);
+        let Some(layout) = self.layout.or(saved) else {
+            // One step up should pay at least the take-profit percentage
+            let Some(layout) =
+                GridLayout::from_touches(&market.touches, self.levels, config.sell_percentage)
+            else {
+                println!("⚠️ Not enough price history to place the grid. Waiting...");
+                return Ok(Intent::Hold(Duration::from_secs(10)));
+            };
+            println!(
+                "🕸️ Grid placed on the most-touched prices: {:.4} → {:.4}, step {:.4}",
+                layout.lower,
+                layout.upper(),
+                layout.step
+            );
+            return Ok(Intent::PlaceGrid(layout));
+        };
+
+        // Lots recorded before the holding went to zero were sold some other way
+        let lots = if position.holding > 0.0 {
+            open_lots(&position.trades)
+        } else {
+            Vec::new()
+        };
+        if let Some(open) = lots.iter().find(|open| price >= open.lot.sell) {
+            println!(
+                "✅ Grid sell: lot {:.4} → {:.4} reached at {:.4}",
+                open.lot.buy, open.lot.sell, price
+            );
+            return Ok(Intent::Sell(SellOrder {
+                amount: open.amount,
+                grid: Some(open.lot),
+            }));
+        }
+
+        let Some(level) = layout.buy_level(price) else {
+            println!(
+                "📏 Price {:.4} outside the buy levels ({:.4} → {:.4})",
+                price,
+                layout.lower,
+                layout.upper() - layout.step
+            );
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        };
+        if lots
+            .iter()
+            .any(|open| (open.lot.buy - level).abs() < layout.step / 2.0)
+        {
+            println!(
+                "🕸️ Price {:.4}: level {:.4} already holds a lot",
+                price, level
+            );
+            return Ok(Intent::Hold(Duration::from_secs(5)));
+        }
+
+        let balance = market.quote_balance.unwrap_or(0.0);
+        let free_lots = (layout.levels as usize - 1)
+            .saturating_sub(lots.len())
+            .max(1);
+        let spend = self
+            .order_size
+            .unwrap_or(balance / free_lots as f64)
+            .min(balance);
+        if spend < config.min_buy_amount {
+            println!("⚠️ Grid order {:.2} too small to execute. Skipping.", spend);
+            return Ok(Intent::Hold(Duration::from_secs(10)));
+        }
+        println!(
+            "🛒 Grid buy at level {:.4} (price {:.4}), selling at {:.4}",
+            level,
+            price,
+            level + layout.step
+        );
+        Ok(Intent::Buy(BuyOrder {
+            spend,
+            dca_level: 0,
+            grid: Some(GridLot {
+                buy: level,
+                sell: level + layout.step,
+            }),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    fn lot(buy: f64) -> GridLot {
+        GridLot {
+            buy,
+            sell: buy + 10.0,
+        }
+    }
+
+    fn grid_buy(state: &mut PairState, buy: f64, spent: f64, received: f64) {
+        state.record_buy(spent, received, 0, Utc::now());
+        state.tag_grid_lot(lot(buy));
+    }
+
+    fn grid_sell(state: &mut PairState, buy: f64, sold: f64, received: f64) {
+        if sold >= state.holding {
+            state.record_sell(sold, received, Utc::now());
+        } else {
+            state.record_partial_sell(sold, received, Utc::now());
+        }
+        state.tag_grid_lot(lot(buy));
+    }
+
+    #[test]
+    fn sells_close_the_lot_of_their_level() {
+        let mut state = PairState::default();
+        grid_buy(&mut state, 120.0, 24.0, 0.2);
+        grid_buy(&mut state, 110.0, 22.0, 0.2);
+        grid_sell(&mut state, 120.0, 0.2, 26.0);
+        grid_buy(&mut state, 120.0, 24.0, 0.2);
+
+        let (levels, lots) = replay_grid(&state.trades);
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[0].lot, lot(110.0));
+        assert_eq!(lots[1].lot, lot(120.0));
+        let level = levels.iter().find(|l| l.lot == lot(120.0)).unwrap();
+        assert_eq!((level.buys, level.sells, level.profit), (2, 1, 2.0));
+
+        // A full exit outside the grid closes every lot
+        state.record_sell(0.4, 50.0, Utc::now());
+        assert!(open_lots(&state.trades).is_empty());
+    }
+
+    #[test]
+    fn buys_the_level_above_the_price_once_and_sells_it_a_step_up() {
+        let config = test_config("grid-decide", "");
+        let mut grid = GridStrategy::new(Some(100.0), Some(140.0), 5, Some(20.0));
+        let market = |price: f64| MarketSnapshot {
+            price: Some(price),
+            quote_balance: Some(100.0),
+            ..MarketSnapshot::default()
+        };
+        let mut state = PairState::default();
+
+        // The top level is only a sell target
+        assert_eq!(
+            grid.decide(&config, &market(135.0), &state).unwrap(),
+            Intent::Hold(Duration::from_secs(5))
+        );
+        assert_eq!(
+            grid.decide(&config, &market(114.0), &state).unwrap(),
+            Intent::Buy(BuyOrder {
+                spend: 20.0,
+                dca_level: 0,
+                grid: Some(lot(120.0)),
+            })
+        );
+        grid_buy(&mut state, 120.0, 20.0, 0.17);
+        assert_eq!(
+            grid.decide(&config, &market(116.0), &state).unwrap(),
+            Intent::Hold(Duration::from_secs(5))
+        );
+        assert_eq!(
+            grid.decide(&config, &market(130.5), &state).unwrap(),
+            Intent::Sell(SellOrder {
+                amount: 0.17,
+                grid: Some(lot(120.0)),
+            })
+        );
+    }
+
+    #[test]
+    fn a_derived_layout_is_saved_and_reused() {
+        let config = test_config("grid-derived", "");
+        let mut grid = GridStrategy::new(None, None, 3, Some(20.0));
+        let market = MarketSnapshot {
+            price: Some(95.0),
+            quote_balance: Some(100.0),
+            touches: vec![(100.0, 9), (110.0, 7)],
+            ..MarketSnapshot::default()
+        };
+        let mut state = PairState::default();
+
+        let Intent::PlaceGrid(layout) = grid.decide(&config, &market, &state).unwrap() else {
+            panic!("expected the grid to be placed");
+        };
+        assert_eq!(layout, GridLayout::new(100.0, 110.0, 3));
+
+        // After a restart the saved layout is used, even once the touches have moved
+        state.grid_layout = Some(layout);
+        let moved = MarketSnapshot {
+            touches: vec![(50.0, 9), (60.0, 7)],
+            ..market
+        };
+        let mut restarted = GridStrategy::new(None, None, 3, Some(20.0));
+        assert_eq!(
+            restarted.decide(&config, &moved, &state).unwrap(),
+            Intent::Hold(Duration::from_secs(5))
+        );
+
+        // Another level count places a new grid
+        let mut wider = GridStrategy::new(None, None, 4, Some(20.0));
+        assert!(matches!(
+            wider.decide(&config, &moved, &state).unwrap(),
+            Intent::PlaceGrid(_)
+        ));
+    }
+}
@@ -1,53 +1,465 @@
 use chrono::{DateTime, Utc};
-use solana_sdk::{signature::Keypair, signer::Signer};
+use std::time::Duration;
 
 use crate::{
-    log_manager::{load_trade_log, log_trade, read_log, send_telegram_message, write_log},
-    market_risk_analyzer::{
-        PriceTouchAnalyzer, fetch_and_log_binance_history, fetch_current_binance_price_from_log,
+    config::{Severity, StrategyConfig},
+    dca_strategy::plan_ladder,
+    error::{BotError, BotResult, ErrorAction, halt, halt_reason},
+    event_log::{EventKind, record_event},
+    grid_strategy::open_lots,
+    market_feed::{LiveFeed, MarketFeed},
+    market_risk_analyzer::{PriceTouchAnalyzer, fetch_current_binance_price_from_log},
+    notifier::notify,
+    portfolio::{Portfolio, portfolio_for},
+    risk_limits,
+    runtime_params::ParamStore,
+    state_store::{PairState, StateStore},
+    strategy::{
+        BuyOrder, DataNeeds, Intent, MarketSnapshot, RiskAssessment, SellOrder, Strategy,
+        build_strategy,
     },
-    utils::{get_usdc_balance, jupiter_swap, sol_get_sol_balance},
+    swap_venue::{SwapFill, SwapVenue},
 };
-use std::env;
+
+/// Trades in a row that may fail before the pair is paused
+const MAX_FAILED_TRADES: u32 = 5;
+
+/// Everything the strategy loop talks to, so the same code runs live and in a backtest
+pub struct StrategyContext<'a> {
+    pub config: &'a StrategyConfig,
+    pub venue: &'a dyn SwapVenue,
+    pub feed: &'a dyn MarketFeed,
+    pub notify: bool,                     // Send Telegram messages (live runs only)
+    pub portfolio: Option<&'a Portfolio>, // Drawdown limit shared with the other pairs
+}
+
+impl StrategyContext<'_> {
+    /// Goes to every configured notifier; a failed notification never stops the strategy
+    async fn notify(&self, severity: Severity, message: &str) {
+        if !self.notify {
+            return;
+        }
+        if self.config.is_paper() {
+            notify(severity, &format!("📝 *[PAPER TRADE]*\n{}", message)).await
+        } else {
+            notify(severity, message).await
+        }
+    }
+
+    /// Keeps what happened for the digests (live runs only)
+    fn record(&self, kind: EventKind, detail: &str) {
+        if self.notify {
+            record_event(&self.config.pair_name(), kind, detail);
+        }
+    }
+
+    /// Applies the error policy; returns `false` when the loop must stop
+    async fn handle_error(
+        &self,
+        error: &BotError,
+        trading_flag: &std::sync::Arc<tokio::sync::Mutex<bool>>,
+    ) -> bool {
+        let pair_name = self.config.pair_name();
+        self.record(EventKind::Error, &error.to_string());
+        match error.action() {
+            ErrorAction::Retry | ErrorAction::SkipIteration => {
+                println!("⚠️ [{}] {} — skipping this iteration", pair_name, error);
+                true
+            }
+            ErrorAction::PausePair => {
+                self.pause(&error.to_string(), trading_flag).await;
+                false
+            }
+            ErrorAction::Halt => {
+                println!("🚨 [{}] Halting the bot: {}", pair_name, error);
+                halt(&format!("{}: {}", pair_name, error));
+                *trading_flag.lock().await = false;
+                self.notify(Severity::Critical, &format!(
+                    "🚨 *TradeRS-bot halted* ({})\n{}\nCheck the pair state files and restart the bot.",
+                    pair_name, error
+                ))
+                .await;
+                false
+            }
+        }
+    }
+
+    /// Stops the pair until /start_trading
+    async fn pause(&self, reason: &str, trading_flag: &std::sync::Arc<tokio::sync::Mutex<bool>>) {
+        let pair_name = self.config.pair_name();
+        println!("⏸️ [{}] Paused: {}", pair_name, reason);
+        *trading_flag.lock().await = false;
+        self.notify(
+            Severity::Warning,
+            &format!(
+                "⏸️ *Trading paused:* {}\n{}\nUse /start\\_trading to resume.",
+                pair_name, reason
+            ),
+        )
+        .await;
+    }
+
+    /// Fetches what the strategy asked for
+    async fn snapshot(
+        &self,
+        config: &StrategyConfig,
+        position: &PairState,
+        needs: DataNeeds,
+        now: DateTime<Utc>,
+    ) -> BotResult<MarketSnapshot> {
+        let mut market = MarketSnapshot {
+            now,
+            ..MarketSnapshot::default()
+        };
+        if needs.risk || needs.price_history {
+            println!("🕒 Checking for Market Condition...");
+            self.read_history(config, needs, &mut market).await?;
+        }
+        if needs.exit_quote && position.holding > 0.0 {
+            // 0.5% slippage tolerance, the swap itself uses `slippage_bps`
+            let quote = self
+                .venue
+                .quote(
+                    &config.left_asset,
+                    &config.right_asset,
+                    position.holding,
+                    50,
+                )
+                .await
+                .inspect_err(|e| println!("❌ Failed to fetch quote for selling: {}", e))?;
+            market.exit_quote = Some(quote.out_amount);
+        }
+        if needs.quote_balance {
+            market.quote_balance = Some(self.venue.balance(&config.right_asset).await?);
+        }
+        Ok(market)
+    }
+
+    /// Refreshes the Binance history, then reads the current price, the touched price buckets
+    /// and how often the take-profit target was touched; left empty when there is no history yet
+    async fn read_history(
+        &self,
+        config: &StrategyConfig,
+        needs: DataNeeds,
+        market: &mut MarketSnapshot,
+    ) -> BotResult<()> {
+        let binance_price_log = config.binance_price_log_path();
+        self.feed
+            .refresh_history(&binance_price_log, &config.binance_symbol)
+            .await
+            .map_err(|e| BotError::Network(format!("Failed to fetch Binance history: {}", e)))?;
+        let Ok(analyzer) = PriceTouchAnalyzer::from_file(&binance_price_log, 0.25) else {
+            return Ok(());
+        };
+        let price = fetch_current_binance_price_from_log(&binance_price_log).map_err(|e| {
+            BotError::Network(format!("Failed to read the current Binance price: {}", e))
+        })?;
+        market.price = Some(price);
+        if needs.price_history {
+            market.touches = analyzer
+                .bucket_counts()
+                .into_iter()
+                .map(|(bucket, count)| (bucket as f64 / 1000.0, count))
+                .collect();
+        }
+        if needs.risk {
+            let (label, touches, multiplier) = analyzer.assess_price(price, config.sell_percentage);
+            println!(
+                "[Risk Check] Price: {:.2} | Touches: {} | Risk: {} | Multiplier: {:.2}",
+                price, touches, label, multiplier
+            );
+            self.record(EventKind::Risk, &label);
+            market.risk = Some(RiskAssessment { label, multiplier });
+        }
+        Ok(())
+    }
+
+    /// Swaps `amount` of `from` into `to`, retrying with more slippage on retryable errors.
+    /// Returns the last error once the attempts run out or on an error that is not retried.
+    async fn swap_with_retries(
+        &self,
+        config: &StrategyConfig,
+        from: &str,
+        to: &str,
+        amount: f64,
+    ) -> BotResult<SwapFill> {
+        let mut trade_retries = config.trade_retries;
+        let mut trade_slippage_bps = config.slippage_bps;
+        while trade_retries > 0 {
+            println!(
+                "💱 Swapping {:.6} {} for {} with slippage {}bps",
+                amount, from, to, trade_slippage_bps
+            );
+            match self.venue.swap(from, to, amount, trade_slippage_bps).await {
+                Ok(fill) => return Ok(fill),
+                Err(e) => {
+                    println!("⚠️ Swap attempt failed: {}", e);
+                    if e.action() != ErrorAction::Retry {
+                        return Err(e);
+                    }
+                    self.record(EventKind::Retry, &e.to_string());
+                    trade_retries -= 1;
+                    if trade_slippage_bps < config.slippage_bps_max {
+                        trade_slippage_bps += 1;
+                    }
+                    if trade_retries > 0 {
+                        println!(
+                            "🔁 Retrying in 2 seconds... ({} trade_retries left)",
+                            trade_retries
+                        );
+                        self.feed.retry_delay(Duration::from_secs(2)).await;
+                    } else {
+                        println!("❌ Max trade_retries reached. Aborting swap.");
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Err(BotError::Config(
+            "`trade_retries` must be at least 1".to_string(),
+        ))
+    }
+
+    /// Buys, records the fill and reports it; a failed swap or an unsaved fill is returned
+    async fn execute_buy(
+        &self,
+        config: &StrategyConfig,
+        store: &StateStore,
+        state: &mut PairState,
+        order: BuyOrder,
+    ) -> BotResult<()> {
+        let (left_asset, right_asset) = (&config.left_asset, &config.right_asset);
+        let BuyOrder {
+            spend,
+            dca_level,
+            grid,
+        } = order;
+        let SwapFill {
+            out_amount: received_amount,
+            tx_signature,
+        } = self
+            .swap_with_retries(config, right_asset, left_asset, spend)
+            .await?;
+        let kind = match grid {
+            Some(lot) => format!("Grid buy at {:.4}", lot.buy),
+            None if dca_level > state.dca_level => format!("DCA buy (level {})", dca_level),
+            None => "Buy".to_string(),
+        };
+        println!(
+            "🎉 {} successful! Received {:.6} {} in tx {}",
+            kind, received_amount, left_asset, tx_signature
+        );
+
+        // Record the buy, its DCA level and the amount received in one save
+        let opens = state.holding == 0.0;
+        state.record_buy(spend, received_amount, dca_level, self.feed.now());
+        if opens {
+            state.ladder = plan_ladder(config, spend, received_amount);
+        }
+        if let Some(lot) = grid {
+            state.tag_grid_lot(lot);
+        }
+        store
+            .save(state)
+            .map_err(|e| Self::unrecorded(&kind, &tx_signature, e))?;
+        self.notify(
+            Severity::Info,
+            &format!(
+                "🎉 *{} successful!* [{}]\nSpent `{:.6}` *{}*, received `{:.6}` *{}* in tx:\n`{}`",
+                kind,
+                config.pair_name(),
+                spend,
+                right_asset,
+                received_amount,
+                left_asset,
+                tx_signature
+            ),
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Sells, records the fill (a full exit closes the position) and reports it;
+    /// a failed swap or an unsaved fill is returned
+    async fn execute_sell(
+        &self,
+        config: &StrategyConfig,
+        store: &StateStore,
+        state: &mut PairState,
+        order: SellOrder,
+    ) -> BotResult<()> {
+        let (left_asset, right_asset) = (&config.left_asset, &config.right_asset);
+        let amount = order.amount.min(state.holding);
+        // Cost of the grid lot, for its profit
+        let lot_cost = order.grid.and_then(|lot| {
+            open_lots(&state.trades)
+                .into_iter()
+                .find(|open| open.lot == lot)
+                .map(|open| open.cost)
+        });
+        let SwapFill {
+            out_amount: received_amount,
+            tx_signature,
+        } = self
+            .swap_with_retries(config, left_asset, right_asset, amount)
+            .await?;
+        println!(
+            "💰 SELL completed! Got {:.6} {} in tx {}",
+            received_amount, right_asset, tx_signature
+        );
+
+        let paid = lot_cost.unwrap_or_else(|| state.open_cost());
+        let profit = (amount >= state.holding || lot_cost.is_some()).then(|| {
+            let profit = received_amount - paid;
+            println!(
+                "📈 Profit: {:+.6} {} ({:+.2}%)",
+                profit,
+                right_asset,
+                (profit / paid) * 100.0
+            );
+            profit
+        });
+        if amount >= state.holding {
+            // Holding and DCA state go back to zero upon full exit
+            state.record_sell(amount, received_amount, self.feed.now());
+        } else {
+            state.record_partial_sell(amount, received_amount, self.feed.now());
+        }
+        if let Some(lot) = order.grid {
+            state.tag_grid_lot(lot);
+        }
+        let kind = match order.grid {
+            Some(lot) => format!("Grid sell at {:.4}", lot.sell),
+            None => "Sell".to_string(),
+        };
+        store
+            .save(state)
+            .map_err(|e| Self::unrecorded(&kind, &tx_signature, e))?;
+        let profit = profit
+            .map(|profit| format!(", profit `{:+.6}` *{}*", profit, right_asset))
+            .unwrap_or_default();
+        self.notify(
+            Severity::Info,
+            &format!(
+                "🚀 *{} successful!* [{}]\nSold `{:.6}` *{}* for `{:.6}` *{}*{} in tx:\n`{}`",
+                kind,
+                config.pair_name(),
+                amount,
+                left_asset,
+                received_amount,
+                right_asset,
+                profit,
+                tx_signature
+            ),
+        )
+        .await;
+        Ok(())
+    }
+
+    /// A fill that could not be saved: the wallet changed but the state on disk does not show it
+    fn unrecorded(kind: &str, tx_signature: &str, error: BotError) -> BotError {
+        BotError::StateIo(format!(
+            "{} filled in tx {} but is NOT recorded: {}",
+            kind, tx_signature, error
+        ))
+    }
+
+    /// Stops the pair until /resume, keeping the reason in its state
+    async fn trip(
+        &self,
+        store: &StateStore,
+        state: &mut PairState,
+        reason: &str,
+        trading_flag: &std::sync::Arc<tokio::sync::Mutex<bool>>,
+    ) -> BotResult<()> {
+        let pair_name = self.config.pair_name();
+        println!("⏸️ [{}] Paused: {}", pair_name, reason);
+        *trading_flag.lock().await = false;
+        state.trip(reason, self.feed.now());
+        store.save(state)?;
+        self.notify(
+            Severity::Warning,
+            &format!(
+                "⏸️ *Trading paused:* {}\n{}\nUse /resume to trade again.",
+                pair_name, reason
+            ),
+        )
+        .await;
+        Ok(())
+    }
+}
 
 pub async fn jup_bot_start(
-    left_asset: &str,
-    right_asset: &str,
-    sell_percentage: f64,
-    dca_recover_percentage: f64,
-    r_factor: f64, // Each DCA level increases USDC amount by r_factor (e.g., 0.5 for 50%)
+    config: &StrategyConfig,
+    venue: &dyn SwapVenue,
+    trading_flag: std::sync::Arc<tokio::sync::Mutex<bool>>,
+) {
+    let ctx = StrategyContext {
+        config,
+        venue,
+        feed: &LiveFeed,
+        notify: true,
+        portfolio: portfolio_for(config),
+    };
+    let mut strategy = build_strategy(config);
+    run_strategy(&ctx, strategy.as_mut(), trading_flag).await;
+}
+
+/// The shared executor: asks the strategy for an intent on every iteration and carries it out
+pub async fn run_strategy(
+    ctx: &StrategyContext<'_>,
+    strategy: &mut dyn Strategy,
     trading_flag: std::sync::Arc<tokio::sync::Mutex<bool>>,
 ) {
-    send_telegram_message(&format!("🟢 TradeRS-bot Online"))
-        .await
-        .unwrap();
-
-    // if let Err(e) = dotenvy::from_path(".env") {
-    //     if cfg!(debug_assertions) {
-    //         eprintln!("⚠️  .env file not found: {e}");
-    //     }
-    // }
-    let wallet_pk = env::var("SOL_WALLET_PK").expect("SOL_WALLET_PK not set in .env");
-    let rpc_url = "https://api.mainnet-beta.solana.com";
-
-    // === 1. Parse mnemonic, derive key and address ===
-    let sol_keypair = Keypair::from_base58_string(&wallet_pk);
-    let wallet_pubkey = sol_keypair.pubkey();
-    println!("✅ Connected Wallet Address: {:?}", wallet_pubkey);
-    let dca_level_path = format!(
-        "logs/solana/pair_{}_{}_dca_level.txt",
-        left_asset, right_asset
+    let pair_name = ctx.config.pair_name();
+
+    ctx.notify(
+        Severity::Info,
+        &format!("🟢 TradeRS-bot Online: *{}*", pair_name),
+    )
+    .await;
+
+    println!(
+        "🔌 Trading {} on venue {} with the {} strategy",
+        pair_name,
+        ctx.venue.name(),
+        strategy.name()
     );
-    let mut current_dca_level: u32 = match std::fs::read_to_string(&dca_level_path) {
-        Ok(content) => content.trim().parse().unwrap_or(0),
-        Err(_) => 0,
+    // Holding, DCA level and trade history, saved together after each fill
+    let store = match StateStore::open(ctx.config) {
+        Ok(store) => store,
+        Err(e) => {
+            ctx.handle_error(&e, &trading_flag).await;
+            return;
+        }
     };
+    // Overrides set with /set, applied over the config file
+    let params = ParamStore::open(ctx.config);
+
+    // A tripped risk limit keeps the pair stopped (e.g. with autostart) until /resume
+    if let Ok(state) = store.load()
+        && let Some(breaker) = state.active_breaker()
+    {
+        println!(
+            "⛔ [{}] Stopped by a risk limit: {}",
+            pair_name, breaker.reason
+        );
+        *trading_flag.lock().await = false;
+        ctx.notify(
+            Severity::Warning,
+            &format!(
+                "⛔ *{}* stays stopped: {}\nUse /resume to trade again.",
+                pair_name, breaker.reason
+            ),
+        )
+        .await;
+        return;
+    }
 
-    let mut trade_log = load_trade_log(&format!(
-        "logs/solana/pair_{left_asset}_{right_asset}_trade_history.json"
-    ))
-    .unwrap();
-    println!("{:?}", trade_log);
+    // Trades that failed since the last fill
+    let mut failed_trades = 0;
+    // Portfolio drawdown trips already acted on, earlier ones do not stop the pair again
+    let mut seen_trips = ctx.portfolio.map_or(0, |portfolio| portfolio.trips());
 
     loop {
         let keep_running = {
@@ -55,437 +467,277 @@ pub async fn jup_bot_start(
             *flag
         };
 
-        if !keep_running {
-            println!("🛑 Trading Succesfully Stop.");
-            send_telegram_message("🛑 Trading Succesfully Stop.")
-                .await
-                .ok();
+        if !keep_running || halt_reason().is_some() {
+            println!("🛑 Trading Succesfully Stop. [{}]", pair_name);
+            ctx.notify(
+                Severity::Info,
+                &format!("🛑 Trading Succesfully Stop: *{}*", pair_name),
+            )
+            .await;
             break;
         }
-        let value = read_log(&format!(
-            "logs/solana/pair_{left_asset}_{right_asset}_value.txt"
-        ))
-        .unwrap();
-        let cooldown_secs = 3600; // 1 hour
-        let now = Utc::now();
-
-        // Trade data section
-        let mut trade_retries = 200;
-        let mut trade_slippage_bps = 1;
-        let trade_slippage_bps_max = 5;
-        let mut smart_adjusted_amount = 0.0;
-        let mut tmp_multip = 0.0;
-
-        match value.eq(&0.0) {
-            true => {
-                // === 1. Cooldown Check ===
-                if let Some(last_trade) = trade_log.first() {
-                    if last_trade.trade_type == "sell" {
-                        let last_time = DateTime::parse_from_rfc3339(&last_trade.time)
-                            .unwrap()
-                            .with_timezone(&Utc);
-                        let elapsed = now.signed_duration_since(last_time).num_seconds();
-
-                        if elapsed < cooldown_secs {
-                            println!(
-                                "⏳ Cooldown active ({}s left). Skipping buy...",
-                                cooldown_secs - elapsed
-                            );
-                            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
-                            continue;
-                        }
-                    }
+        // Reloaded on every iteration so external changes (e.g. reconciliation) are picked up
+        let decision: BotResult<_> = async {
+            let state = store.load()?;
+            let config = params.effective(ctx.config)?;
+            let now = ctx.feed.now();
+            let needs = risk_limits::needs(&config, &state, strategy.needs(&config, &state, now));
+            let market = ctx.snapshot(&config, &state, needs, now).await?;
+            let intent = strategy.decide(&config, &market, &state)?;
+            Ok((intent, config, state, market))
+        }
+        .await;
+        let (intent, config, mut state, market) = match decision {
+            Ok(decision) => decision,
+            Err(e) => {
+                if !ctx.handle_error(&e, &trading_flag).await {
+                    break;
                 }
+                ctx.feed.tick(Duration::from_secs(10)).await;
+                continue;
+            }
+        };
 
-                // === 2. Market Risk Check ===
-                println!("🕒 Checking for Market Condition...");
-                let binance_price_log =
-                    format!("logs/solana/binance_{left_asset}_{right_asset}__prices.csv");
-
-                if let Err(e) =
-                    fetch_and_log_binance_history(&binance_price_log, "SOLUSDT", "4").await
+        // The portfolio drawdown limit sums the equity of every pair and stops them all
+        let drawdown = match ctx.portfolio {
+            Some(portfolio) => {
+                if let Some(equity) = risk_limits::equity(&config, &market, &state)
+                    && let Err(e) = portfolio.report(&pair_name, equity)
+                    && !ctx.handle_error(&e, &trading_flag).await
                 {
-                    println!("⚠️ Failed to fetch Binance history: {}", e);
-                    continue;
+                    break;
                 }
+                portfolio.tripped_since(&mut seen_trips)
+            }
+            None => None,
+        };
 
-                if let Ok(analyzer) = PriceTouchAnalyzer::from_file(&binance_price_log, 0.25) {
-                    let current_price =
-                        fetch_current_binance_price_from_log(&binance_price_log).unwrap();
-                    let (risk_label, touches, multiplier) =
-                        analyzer.assess_price(current_price, sell_percentage);
-                    tmp_multip = multiplier; // f64 is Copy, no need to clone
-                    let log_line = format!(
-                        "[Risk Check] Price: {:.2} | Touches: {} | Risk: {} | Multiplier: {:.2}",
-                        current_price, touches, risk_label, multiplier
-                    );
-                    println!("{}", log_line);
-
-                    // Calculate the adjusted amount to use based on current USDC balance
-                    let adjusted_amount = get_usdc_balance(
-                        &wallet_pubkey.to_string(),
-                        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
-                    )
-                    .await
-                        * multiplier;
-
-                    match risk_label.as_str() {
-                        "🔴 HIGH-RISK" => {
-                            println!("❌ Skipping trade due to HIGH RISK.");
-                            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-                            continue;
-                        }
-                        "⚠️ WEAK ZONE" => {
-                            println!(
-                                "⚠️ Weak zone detected. Reducing trade size to {:.2}.",
-                                adjusted_amount
-                            );
-                        }
-                        _ => {
-                            println!(
-                                "✅ Risk acceptable. Using adjusted size: {:.2}",
-                                adjusted_amount
-                            );
-                        }
-                    }
-
-                    // Ensure the adjusted amount meets a minimum threshold
-                    if adjusted_amount < 10.0 {
-                        println!(
-                            "⚠️ Adjusted amount {:.2} too small to execute. Skipping.",
-                            adjusted_amount
-                        );
-                        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
-                        continue;
-                    } else {
-                        smart_adjusted_amount = adjusted_amount;
-                    }
+        // A fill goes straight to the next iteration, a failed trade waits a tick
+        let traded = match risk_limits::guard(&config, &market, &state, drawdown.as_deref(), intent)
+        {
+            Intent::Hold(wait) => {
+                ctx.feed.tick(wait).await;
+                Ok(())
+            }
+            Intent::Trail(peak) => {
+                state.trailing_peak = Some(peak);
+                if let Err(e) = store.save(&state)
+                    && !ctx.handle_error(&e, &trading_flag).await
+                {
+                    break;
                 }
-
-                // === 3. Proceed with First Trade (Market Buy) ===
-                println!("🚀 Market & Risk Passed. Preparing to Buy...");
-
-                let asset_b_balance = sol_get_sol_balance(rpc_url, &wallet_pubkey).await.unwrap();
-                println!("Account Balance: {:?} SOL", asset_b_balance);
-
-                while trade_retries > 0 {
-                    println!(
-                        "💵 Attempting to buy {:.6} worth of {} with slippage {}bps",
-                        smart_adjusted_amount, left_asset, trade_slippage_bps
-                    );
-
-                    match jupiter_swap(
-                        rpc_url,
-                        right_asset, // USDC – what you have
-                        left_asset,  // SOL – what you want to buy
-                        smart_adjusted_amount,
-                        trade_slippage_bps,
-                        &sol_keypair,
-                    )
-                    .await
-                    {
-                        Ok((received_amount, tx_signature)) => {
-                            println!(
-                                "🎉 Buy successful! Received {:.6} {} in tx {}",
-                                received_amount, left_asset, tx_signature
-                            );
-
-                            // Log the buy trade
-                            log_trade(
-                                &format!(
-                                    "logs/solana/pair_{}_{}_trade_history.json",
-                                    left_asset, right_asset
-                                ),
-                                &mut trade_log,
-                                "buy",
-                                smart_adjusted_amount, // USDC spent
-                                received_amount,       // SOL received
-                                Some(current_dca_level),
-                            )
-                            .unwrap();
-
-                            // Telegram notificator
-                            send_telegram_message(&format!(
-                                "🎉 *Buy successful!*\nReceived `{:.6}` *{}* in tx:\n`{}`",
-                                received_amount, left_asset, tx_signature
-                            ))
-                            .await
-                            .unwrap();
-
-                            // Write the newly received SOL to the value file
-                            let current_val = value;
-                            let new_val = current_val + received_amount;
-                            write_log(
-                                &format!(
-                                    "logs/solana/pair_{}_{}_value.txt",
-                                    left_asset, right_asset
-                                ),
-                                &new_val.to_string(),
-                            )
-                            .unwrap();
-
-                            break;
-                        }
-                        Err(e) => {
-                            println!("⚠️ Swap attempt failed: {:?}", e);
-                            trade_retries -= 1;
-                            if trade_slippage_bps < trade_slippage_bps_max {
-                                trade_slippage_bps += 1;
-                            }
-                            if trade_retries > 0 {
-                                println!(
-                                    "🔁 Retrying in 2 seconds... ({} trade_retries left)",
-                                    trade_retries
-                                );
-                                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                            } else {
-                                println!("❌ Max trade_retries reached. Aborting swap.");
-                                break;
-                            }
-                        }
+                ctx.feed.tick(Duration::from_secs(5)).await;
+                Ok(())
+            }
+            Intent::PlaceGrid(layout) => {
+                state.grid_layout = Some(layout);
+                if let Err(e) = store.save(&state)
+                    && !ctx.handle_error(&e, &trading_flag).await
+                {
+                    break;
+                }
+                Ok(())
+            }
+            Intent::Buy(order) => ctx.execute_buy(&config, &store, &mut state, order).await,
+            Intent::Sell(order) => ctx.execute_sell(&config, &store, &mut state, order).await,
+            Intent::Pause(reason) => {
+                if let Err(e) = ctx.trip(&store, &mut state, &reason, &trading_flag).await {
+                    ctx.handle_error(&e, &trading_flag).await;
+                }
+                break;
+            }
+            Intent::Exit(reason) => {
+                let order = SellOrder {
+                    amount: state.holding,
+                    grid: None,
+                };
+                let sold = ctx.execute_sell(&config, &store, &mut state, order).await;
+                if sold.is_ok() {
+                    if let Err(e) = ctx.trip(&store, &mut state, &reason, &trading_flag).await {
+                        ctx.handle_error(&e, &trading_flag).await;
                     }
+                    break;
                 }
+                sold
             }
-            false => {
-                println!("📈 Checking SELL conditions...");
+        };
 
-                // ---- NEW: Compute the open position only using trades after the most recent SELL ----
-                let open_trades: Vec<&_> = if let Some(last_sell_idx) =
-                    trade_log.iter().rposition(|t| t.trade_type == "sell")
+        match traded {
+            Ok(()) => failed_trades = 0,
+            Err(e) => {
+                failed_trades += 1;
+                // An unrecorded fill halts rather than counting as a failed trade
+                let keep_running = if failed_trades >= MAX_FAILED_TRADES
+                    && e.action() != ErrorAction::Halt
                 {
-                    trade_log.iter().skip(last_sell_idx + 1).collect()
+                    ctx.record(EventKind::Error, &e.to_string());
+                    let reason = format!("{} trades failed in a row, last: {}", failed_trades, e);
+                    ctx.pause(&reason, &trading_flag).await;
+                    false
                 } else {
-                    trade_log.iter().collect()
+                    ctx.handle_error(&e, &trading_flag).await
                 };
-
-                let sol_holding = read_log(&format!(
-                    "logs/solana/pair_{}_{}_value.txt",
-                    left_asset, right_asset
-                ))
-                .unwrap();
-                let paid_usdc: f64 = open_trades.iter().map(|trade| trade.amount_token_a).sum();
-                let average_entry_price = paid_usdc / sol_holding;
-
-                println!("🔁 Holding: {:.6} SOL → ", sol_holding,);
-
-                // === 2. Get quote for selling that SOL to USDC ===
-                let amount_lamports = (sol_holding * 1_000_000_000.0) as u64;
-                let quote_url = format!(
-                    "https://quote-api.jup.ag/v6/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-                    left_asset,  // SOL
-                    right_asset, // USDC
-                    amount_lamports,
-                    50 // 0.5% slippage tolerance
-                );
-
-                let client = reqwest::Client::new();
-                let quote_resp = client.get(&quote_url).send().await;
-                if let Ok(resp) = quote_resp {
-                    let quote_json: serde_json::Value = resp.json().await.unwrap();
-                    let out_amount_str = quote_json["outAmount"]
-                        .as_str()
-                        .ok_or("Missing outAmount in quote")
-                        .unwrap();
-                    let usdc_received = out_amount_str.parse::<f64>().unwrap() / 1_000_000.0;
-
-                    println!(
-                        "🔁 Would return {:.6} USDC for selling {:.6} SOL",
-                        usdc_received, sol_holding
-                    );
-
-                    // === 3. Check if it's profitable ===
-                    let target_return = paid_usdc * (1.0 + sell_percentage / 100.0);
-                    println!(
-                        "🎯 Need at least {:.6} USDC to sell for profit (+{}%)",
-                        target_return, sell_percentage
-                    );
-
-                    if usdc_received >= target_return {
-                        println!("✅ SELL opportunity detected!");
-
-                        while trade_retries > 0 {
-                            println!(
-                                "💰 Attempting to sell {:.6} SOL with {}bps slippage...",
-                                sol_holding, trade_slippage_bps
-                            );
-                            match jupiter_swap(
-                                rpc_url,
-                                left_asset,  // selling SOL
-                                right_asset, // buying USDC
-                                sol_holding,
-                                trade_slippage_bps,
-                                &sol_keypair,
-                            )
-                            .await
-                            {
-                                Ok((usdc_received_actual, tx_signature)) => {
-                                    let profit = usdc_received_actual - paid_usdc;
-                                    println!(
-                                        "💰 SELL completed! Got {:.6} USDC in tx {}",
-                                        usdc_received_actual, tx_signature
-                                    );
-
-                                    // Reset DCA state upon full exit
-                                    std::fs::write(&dca_level_path, "0").unwrap();
-                                    current_dca_level = 0;
-                                    println!(
-                                        "📈 Profit: +{:.6} USDC (+{:.2}%)",
-                                        profit,
-                                        (profit / paid_usdc) * 100.0
-                                    );
-
-                                    log_trade(
-                                        &format!(
-                                            "logs/solana/pair_{}_{}_trade_history.json",
-                                            left_asset, right_asset
-                                        ),
-                                        &mut trade_log,
-                                        "sell",
-                                        sol_holding,
-                                        usdc_received_actual,
-                                        Some(current_dca_level),
-                                    )
-                                    .unwrap();
-                                    send_telegram_message(&format!(
-                                        "🚀 *Sell successful!*\nReceived `{:.6}` Selling: *{}* Sol`",
-                                        usdc_received_actual, sol_holding
-                                    ))
-                                    .await
-                                    .unwrap();
-                                    write_log(
-                                        &format!(
-                                            "logs/solana/pair_{}_{}_value.txt",
-                                            left_asset, right_asset
-                                        ),
-                                        &"0.0".to_string(),
-                                    )
-                                    .unwrap();
-
-                                    break;
-                                }
-                                Err(e) => {
-                                    println!("⚠️ Sell attempt failed: {:?}", e);
-                                    trade_retries -= 1;
-                                    if trade_slippage_bps < trade_slippage_bps_max {
-                                        trade_slippage_bps += 1;
-                                    }
-                                    if trade_retries > 0 {
-                                        println!(
-                                            "🔁 Retrying in 2 seconds... ({} trade_retries left)",
-                                            trade_retries
-                                        );
-                                        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                                    } else {
-                                        println!("❌ Max trade_retries reached. Aborting sell.");
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        let price_change = 100.0 * (usdc_received / paid_usdc - 1.0);
-                        println!("📉 Price is at {:+.2}%", price_change);
-
-                        if price_change <= -dca_recover_percentage {
-                            println!("🛒 DCA Triggered! Buying the dip...");
-                            current_dca_level += 1;
-
-                            // Save updated DCA level
-                            std::fs::write(&dca_level_path, current_dca_level.to_string()).unwrap();
-
-                            let tmp_multip = if tmp_multip == 0.0 { 1.0 } else { tmp_multip };
-                            let dca_amount = (get_usdc_balance(
-                                &wallet_pubkey.to_string(),
-                                "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
-                            )
-                            .await
-                                * tmp_multip)
-                                * r_factor;
-                            if dca_amount < 5.0 {
-                                println!("⚠️ DCA amount too small ({:.2}). Skipping.", dca_amount);
-                                continue;
-                            }
-                            println!("🔁 DCA Buy: Investing {:.2} USDC", dca_amount);
-
-                            while trade_retries > 0 {
-                                println!(
-                                    "💵 DCA: Attempting to buy {:.6} worth of {} with slippage {}bps",
-                                    dca_amount, left_asset, trade_slippage_bps
-                                );
-                                match jupiter_swap(
-                                    rpc_url,
-                                    right_asset,
-                                    left_asset,
-                                    dca_amount,
-                                    trade_slippage_bps,
-                                    &sol_keypair,
-                                )
-                                .await
-                                {
-                                    Ok((received_amount, tx_signature)) => {
-                                        println!(
-                                            "🎯 DCA buy successful! Got {:.6} {} in tx {}",
-                                            received_amount, left_asset, tx_signature
-                                        );
-
-                                        // Log the DCA buy trade
-                                        log_trade(
-                                            &format!(
-                                                "logs/solana/pair_{}_{}_trade_history.json",
-                                                left_asset, right_asset
-                                            ),
-                                            &mut trade_log,
-                                            "buy",
-                                            dca_amount,
-                                            received_amount,
-                                            Some(current_dca_level),
-                                        )
-                                        .unwrap();
-
-                                        // Update the value.txt by adding the new SOL received
-                                        let current_val = value;
-                                        let new_val = current_val + received_amount;
-                                        write_log(
-                                            &format!(
-                                                "logs/solana/pair_{}_{}_value.txt",
-                                                left_asset, right_asset
-                                            ),
-                                            &new_val.to_string(),
-                                        )
-                                        .unwrap();
-
-                                        break;
-                                    }
-                                    Err(e) => {
-                                        println!("⚠️ DCA Swap failed: {:?}", e);
-                                        trade_retries -= 1;
-                                        if trade_slippage_bps < trade_slippage_bps_max {
-                                            trade_slippage_bps += 1;
-                                        }
-                                        if trade_retries > 0 {
-                                            println!(
-                                                "🔁 Retrying DCA in 2 seconds... ({} trade_retries left)",
-                                                trade_retries
-                                            );
-                                            tokio::time::sleep(std::time::Duration::from_secs(2))
-                                                .await;
-                                        } else {
-                                            println!(
-                                                "❌ Max trade_retries reached for DCA. Aborting."
-                                            );
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        } else {
-                            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-                        }
-                    }
-                } else {
-                    println!("❌ Failed to fetch quote for selling.");
+                if !keep_running {
+                    break;
                 }
+                ctx.feed.tick(Duration::from_secs(10)).await;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::swap_venue::{SwapQuote, VenueResult};
+    use async_trait::async_trait;
+    use std::sync::Mutex as StdMutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails every swap with `error`
+    struct RejectingVenue {
+        swaps: AtomicU32,
+        error: fn() -> BotError,
+    }
+
+    /// The way an on-chain `InstructionError` fails
+    fn instruction_error() -> BotError {
+        BotError::VenueRejected("InstructionError(2, Custom(1))".to_string())
+    }
+
+    #[async_trait]
+    impl SwapVenue for RejectingVenue {
+        fn name(&self) -> &str {
+            "rejecting"
+        }
+
+        async fn quote(&self, _: &str, _: &str, _: f64, _: u64) -> VenueResult<SwapQuote> {
+            Ok(SwapQuote { out_amount: 1.0 })
+        }
+
+        async fn swap(&self, _: &str, _: &str, _: f64, _: u64) -> VenueResult<SwapFill> {
+            self.swaps.fetch_add(1, Ordering::SeqCst);
+            Err((self.error)())
+        }
+
+        async fn balance(&self, _: &str) -> VenueResult<f64> {
+            Ok(1000.0)
+        }
+    }
+
+    /// A clock that only moves when the loop waits
+    struct ClockFeed {
+        now: StdMutex<DateTime<Utc>>,
+        waited: StdMutex<Duration>,
+    }
+
+    #[async_trait]
+    impl MarketFeed for ClockFeed {
+        fn now(&self) -> DateTime<Utc> {
+            *self.now.lock().unwrap()
+        }
+
+        async fn refresh_history(&self, _: &str, _: &str) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn tick(&self, duration: Duration) {
+            *self.waited.lock().unwrap() += duration;
+            *self.now.lock().unwrap() += chrono::Duration::from_std(duration).unwrap();
+        }
+
+        async fn retry_delay(&self, duration: Duration) {
+            self.tick(duration).await;
+        }
+    }
+
+    fn clock_feed() -> ClockFeed {
+        ClockFeed {
+            now: StdMutex::new(Utc::now()),
+            waited: StdMutex::new(Duration::ZERO),
+        }
+    }
+
+    struct AlwaysBuy;
+
+    impl Strategy for AlwaysBuy {
+        fn name(&self) -> &'static str {
+            "always-buy"
+        }
+
+        fn needs(&self, _: &StrategyConfig, _: &PairState, _: DateTime<Utc>) -> DataNeeds {
+            DataNeeds::default()
+        }
+
+        fn decide(
+            &mut self,
+            _: &StrategyConfig,
+            _: &MarketSnapshot,
+            _: &PairState,
+        ) -> BotResult<Intent> {
+            Ok(Intent::Buy(BuyOrder {
+                spend: 10.0,
+                dca_level: 0,
+                grid: None,
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_swaps_back_off_and_pause_the_pair() {
+        let config = test_config("rejected-swaps", "");
+        let venue = RejectingVenue {
+            swaps: AtomicU32::new(0),
+            error: instruction_error,
+        };
+        let feed = clock_feed();
+        let ctx = StrategyContext {
+            config: &config,
+            venue: &venue,
+            feed: &feed,
+            notify: false,
+            portfolio: None,
+        };
+        let trading_flag = std::sync::Arc::new(tokio::sync::Mutex::new(true));
+
+        run_strategy(&ctx, &mut AlwaysBuy, trading_flag.clone()).await;
+
+        // Not retried: one swap per iteration, a tick after each failure, then paused
+        assert_eq!(venue.swaps.load(Ordering::SeqCst), MAX_FAILED_TRADES);
+        assert_eq!(
+            *feed.waited.lock().unwrap(),
+            Duration::from_secs(10) * (MAX_FAILED_TRADES - 1)
+        );
+        assert!(!*trading_flag.lock().await);
+        assert_eq!(
+            StateStore::open(&config).unwrap().load().unwrap().holding,
+            0.0
+        );
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_swaps_are_not_retried() {
+        let config = test_config("unconfirmed-swap", "trade_retries = 3");
+        let venue = RejectingVenue {
+            swaps: AtomicU32::new(0),
+            error: || BotError::Unconfirmed("swap 5xyz may have landed".to_string()),
+        };
+        let feed = clock_feed();
+        let ctx = StrategyContext {
+            config: &config,
+            venue: &venue,
+            feed: &feed,
+            notify: false,
+            portfolio: None,
+        };
+
+        let result = ctx
+            .swap_with_retries(&config, &config.right_asset, &config.left_asset, 10.0)
+            .await;
+
+        assert!(matches!(result, Err(BotError::Unconfirmed(_))));
+        assert_eq!(venue.swaps.load(Ordering::SeqCst), 1);
+        assert_eq!(*feed.waited.lock().unwrap(), Duration::ZERO);
+    }
+}
@@ -1,14 +1,21 @@
-use chrono::Utc;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::env;
-use std::path::Path;
-use std::sync::Arc;
 use std::{
-    fs::{File, OpenOptions},
-    io::{self, Read, Write},
+    fs::{self, File},
+    io::{self, Write},
 };
-use tokio::sync::Mutex;
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::commands::{COMMANDS, Command, CommandRunner, Reply};
+use crate::config::{Role, TelegramConfig};
+use crate::error::BotResult;
+use crate::grid_strategy::GridLot;
+use crate::manual_trade::{PendingTrades, execute};
+use crate::state_store::write_atomic;
+use crate::telegram_auth::Authorizer;
+use crate::telegram_outbox;
+use crate::telegram_webhook::serve_webhook;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Trade {
@@ -17,6 +24,8 @@ pub struct Trade {
     pub amount_token_b: f64,
     pub time: String,
     pub dca_level: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<GridLot>, // Set on the buy and the sell of a grid lot
 }
 
 pub fn write_log(file_path: &str, log: &str) -> io::Result<()> {
@@ -25,79 +34,30 @@ pub fn write_log(file_path: &str, log: &str) -> io::Result<()> {
     Ok(())
 }
 
-pub fn read_log(file_path: &str) -> io::Result<f64> {
-    if let Ok(mut file) = File::open(file_path) {
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
-        let number: f64 = content.trim().parse().unwrap_or(0.0); // Default to 0.0 if parsing fails
-        Ok(number)
-    } else {
-        File::create(file_path).unwrap();
-        Ok(0 as f64)
-    }
-}
-
-pub fn append_log(file_path: &str, log: &str) -> io::Result<()> {
-    let mut file_path = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(file_path)?; // Open the file in append mode
-    file_path.write_all(log.as_bytes())?; // Write the log as bytes
-    Ok(())
-}
-
-pub fn log_trade(
-    file_path: &str,
-    trade_log: &mut Vec<Trade>,
-    trade_type: &str,
-    amount_token_a: f64,
-    amount_token_b: f64,
-    dca_level: Option<u32>,
-) -> io::Result<()> {
-    let trade = Trade {
-        trade_type: trade_type.to_string(),
-        amount_token_a,
-        amount_token_b,
-        time: Utc::now().to_rfc3339(),
-        dca_level,
-    };
-    trade_log.push(trade.clone()); // Append the trade to the log (list)
-    let trade_json = serde_json::to_string(&trade)?; // Serialize the trade to JSON
-    append_log(file_path, &format!("{}\n", trade_json))?; // Append trade to the log file
-    Ok(())
-}
-
-pub fn load_trade_log(file_path: &str) -> io::Result<Vec<Trade>> {
-    if Path::new(file_path).exists() {
-        let mut file = File::open(file_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-
-        let trades: Vec<Trade> = contents
-            .lines()
-            .filter_map(|line| serde_json::from_str(line).ok()) // Deserialize each line into a Trade object
-            .collect();
-
-        Ok(trades) // ✅ Return the full trade history
-    } else {
-        println!("load_trade_log: File {} does not exist.", file_path);
-        File::create(file_path).unwrap();
-        Ok(Vec::new())
-    }
-}
-
 // Start of Telegram API
 
 #[derive(Debug, Deserialize)]
 struct Update {
     update_id: i64,
     message: Option<Message>,
+    callback_query: Option<CallbackQuery>,
+}
+
+/// Sent when an inline keyboard button is pressed
+#[derive(Debug, Deserialize)]
+struct CallbackQuery {
+    id: String,
+    from: User,
+    data: Option<String>,
+    message: Option<Message>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Message {
+    message_id: i64,
     text: Option<String>,
     chat: Chat,
+    from: Option<User>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -105,87 +65,207 @@ struct Chat {
     id: i64,
 }
 
-pub async fn telegram_command_listener(left_asset: &str, right_asset: &str, sell_percentage: f64, trading_flag: Arc<Mutex<bool>>) {
-    // if let Err(e) = dotenvy::from_path(".env") {
-    //     if cfg!(debug_assertions) {
-    //         eprintln!("⚠️  .env file not found: {e}");
-    //     }
-    // }
+#[derive(Debug, Deserialize)]
+struct User {
+    id: i64,
+    username: Option<String>,
+}
 
-    let client = Client::new();
-    let mut last_update_id: i64 = 0;
+/// Last handled update id, saved so a restart never runs a command twice
+struct UpdateOffset {
+    path: String, // e.g. `logs/solana/telegram_offset.json`
+    last_update_id: Option<i64>, // `None` before the first update ever handled
+}
+
+impl UpdateOffset {
+    fn open(path: &str) -> Self {
+        let last_update_id = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str::<serde_json::Value>(&content)
+                .ok()
+                .and_then(|value| value["last_update_id"].as_i64()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                eprintln!("⚠️ Failed to read {}: {}", path, e);
+                None
+            }
+        };
+        Self {
+            path: path.to_string(),
+            last_update_id,
+        }
+    }
+
+    fn is_new(&self, update_id: i64) -> bool {
+        self.last_update_id.is_none_or(|last| update_id > last)
+    }
+
+    fn advance(&mut self, update_id: i64) {
+        self.last_update_id = Some(update_id);
+        let content = serde_json::json!({ "last_update_id": update_id }).to_string();
+        if let Err(e) = write_atomic(&self.path, &content) {
+            eprintln!("⚠️ Failed to save the Telegram update offset: {}", e);
+        }
+    }
+}
+
+/// Runs the commands of the configured chat, whether updates come from polling or the webhook
+struct CommandHandler {
+    runner: CommandRunner,
+    authorizer: Authorizer,
+    pending_trades: PendingTrades,
+    chat_id: String,
+    offset: UpdateOffset,
+}
+
+impl CommandHandler {
+    async fn handle(&mut self, update: serde_json::Value) {
+        let update: Update = match serde_json::from_value(update.clone()) {
+            Ok(update) => update,
+            Err(e) => {
+                eprintln!("⚠️ Skipping malformed Telegram update: {}", e);
+                if let Some(update_id) = update["update_id"].as_i64() {
+                    self.offset.advance(update_id);
+                }
+                return;
+            }
+        };
+        if !self.offset.is_new(update.update_id) {
+            return;
+        }
+        // Saved first: a command that crashes the bot must not run again on restart
+        self.offset.advance(update.update_id);
+
+        let Self {
+            runner,
+            authorizer,
+            pending_trades,
+            chat_id: telegram_chat_id,
+            ..
+        } = self;
+
+        if let Some(msg) = &update.message
+            && msg.chat.id.to_string() == *telegram_chat_id
+            && let Some(text) = &msg.text
+            && text.starts_with('/')
+        {
+            let user_id = msg.from.as_ref().map(|user| user.id);
+            let username = msg.from.as_ref().and_then(|user| user.username.as_deref());
+            // Authorized first: unauthorized members never reach the parser
+            if let Err(denied) = authorizer.authorize(user_id, username, text) {
+                send_telegram_message(&denied).await.ok();
+                return;
+            }
+            match Command::parse(text) {
+                Ok(command) => {
+                    let role = authorizer.role_of(user_id).unwrap_or(Role::Viewer);
+                    for reply in runner.run(command, role).await {
+                        send_reply(reply, user_id, pending_trades).await;
+                    }
+                }
+                Err(usage) => {
+                    send_telegram_message(&usage).await.ok();
+                }
+            }
+        }
+        if let Some(query) = &update.callback_query
+            && query
+                .message
+                .as_ref()
+                .is_some_and(|msg| msg.chat.id.to_string() == *telegram_chat_id)
+        {
+            handle_trade_button(query, authorizer, pending_trades).await;
+        }
+    }
+}
+
+pub async fn telegram_command_listener(
+    runner: CommandRunner,
+    authorizer: Authorizer,
+    telegram: &TelegramConfig,
+    offset_path: &str,
+) {
     let telegram_http_api =
         env::var("TELEGRAM_HTTP_API").expect("TELEGRAM_HTTP_API not set in .env");
     let telegram_chat_id = env::var("TELEGRAM_CHAT_ID").expect("TELEGRAM_CHAT_ID not set in .env");
+    let mut handler = CommandHandler {
+        runner,
+        authorizer,
+        pending_trades: PendingTrades::default(),
+        chat_id: telegram_chat_id,
+        offset: UpdateOffset::open(offset_path),
+    };
+    // The command menu of Telegram clients, generated from the registry
+    let menu: Vec<serde_json::Value> = COMMANDS
+        .iter()
+        .map(|spec| serde_json::json!({ "command": spec.name, "description": spec.help }))
+        .collect();
+    telegram_outbox::call(
+        "setMyCommands",
+        &[("commands", serde_json::Value::from(menu).to_string())],
+        false,
+    )
+    .ok();
+
+    // On the very first start, commands sent before it are dropped rather than run
+    let first_start = handler.offset.last_update_id.is_none();
+
+    let Some(webhook) = &telegram.webhook else {
+        poll_updates(&telegram_http_api, first_start, &mut handler).await;
+        return;
+    };
+    let (sender, mut receiver) = unbounded_channel();
+    let server = tokio::spawn(serve_webhook(
+        webhook.clone(),
+        telegram_http_api,
+        first_start,
+        sender,
+    ));
+    while let Some(update) = receiver.recv().await {
+        handler.handle(update).await;
+    }
+    if let Ok(Err(e)) = server.await {
+        eprintln!("❌ Telegram webhook stopped, commands are disabled: {}", e);
+    }
+    // The pairs keep trading without commands
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Long-polls getUpdates from the saved offset
+async fn poll_updates(telegram_http_api: &str, first_start: bool, handler: &mut CommandHandler) {
+    let client = Client::new();
+    // getUpdates is refused while a webhook is set, e.g. after leaving webhook mode
+    let url = format!("https://api.telegram.org/bot{}/deleteWebhook", telegram_http_api);
+    if let Err(e) = client.post(&url).send().await {
+        eprintln!("⚠️ Failed to delete the Telegram webhook: {}", e);
+    }
+    if first_start {
+        let url = format!(
+            "https://api.telegram.org/bot{}/getUpdates?offset=-1",
+            telegram_http_api
+        );
+        if let Ok(response) = client.get(&url).send().await
+            && let Ok(json) = response.json::<serde_json::Value>().await
+            && let Some(update_id) = json["result"][0]["update_id"].as_i64()
+        {
+            println!("⏭️ Skipping Telegram updates sent before the first start");
+            handler.offset.advance(update_id);
+        }
+    }
 
     loop {
         let url = format!(
             "https://api.telegram.org/bot{}/getUpdates?offset={}&timeout=10",
             telegram_http_api,
-            last_update_id + 1
+            handler.offset.last_update_id.map_or(0, |last| last + 1)
         );
 
         match client.get(&url).send().await {
             Ok(response) => {
-                if let Ok(json) = response.json::<serde_json::Value>().await {
-                    if let Some(results) = json["result"].as_array() {
-                        for update in results {
-                            let update: Update = serde_json::from_value(update.clone()).unwrap();
-                            if let Some(msg) = &update.message {
-                                if msg.chat.id.to_string() == telegram_chat_id {
-                                    if let Some(text) = &msg.text {
-                                        match text.as_str() {
-                                            "/status" => {
-                                                let status = if *trading_flag.lock().await {
-                                                    "🟢 Bot is Online"
-                                                } else {
-                                                    "🔴 Bot is Offline"
-                                                };
-                                                send_telegram_message(status).await.ok();
-                                            }
-                                            "/start_trading" => {
-                                                *trading_flag.lock().await = true;
-                                                send_telegram_message("✅ Trading Started")
-                                                    .await
-                                                    .ok();
-                                            }
-                                            "/stop_trading" => {
-                                                let mut flag = trading_flag.lock().await;
-                                                if *flag {
-                                                    *flag = false;
-                                                    send_telegram_message("🛑 Safe Stop Triggered")
-                                                        .await
-                                                        .ok();
-                                                } else {
-                                                    send_telegram_message(
-                                                        "⚠️ Trading already stopped.",
-                                                    )
-                                                    .await
-                                                    .ok();
-                                                }
-                                            }
-                                            "/market_status" => {
-                                                match generate_market_status(left_asset, right_asset, sell_percentage).await {
-                                                    Ok(summary) => {
-                                                        send_telegram_message(&summary).await.ok();
-                                                    }
-                                                    Err(e) => {
-                                                        send_telegram_message(&format!(
-                                                            "❌ Failed to get market status: {e}"
-                                                        ))
-                                                        .await
-                                                        .ok();
-                                                    }
-                                                }
-                                            }
-
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                            last_update_id = update.update_id;
-                        }
+                if let Ok(json) = response.json::<serde_json::Value>().await
+                    && let Some(results) = json["result"].as_array()
+                {
+                    for update in results {
+                        handler.handle(update.clone()).await;
                     }
                 }
             }
@@ -196,94 +276,133 @@ pub async fn telegram_command_listener(left_asset: &str, right_asset: &str, sell
     }
 }
 
-pub async fn send_telegram_message(message: &str) -> Result<(), reqwest::Error> {
-    // if let Err(e) = dotenvy::from_path(".env") {
-    //     if cfg!(debug_assertions) {
-    //         eprintln!("⚠️  .env file not found: {e}");
-    //     }
-    // }
-    let telegram_http_api =
-        env::var("TELEGRAM_HTTP_API").expect("TELEGRAM_HTTP_API not set in .env");
-    let telegram_chat_id = env::var("TELEGRAM_CHAT_ID").expect("TELEGRAM_CHAT_ID not set in .env");
-
-    let url = format!(
-        "https://api.telegram.org/bot{}/sendMessage",
-        telegram_http_api
-    );
-
-    let client = reqwest::Client::new();
-    let res = client
-        .post(&url)
-        .form(&[
-            ("chat_id", telegram_chat_id),
-            ("text", message.to_string()),
-            ("parse_mode", "Markdown".to_string()),
-        ])
-        .send()
-        .await?;
-
-    if res.status().is_success() {
-        println!("✅ Message sent to Telegram!");
-    } else {
-        eprintln!(
-            "❌ Failed to send: {}",
-            res.text().await.unwrap_or_default()
-        );
+/// Sends a command reply; trade previews get Confirm / Cancel buttons
+async fn send_reply(reply: Reply, user_id: Option<i64>, pending_trades: &mut PendingTrades) {
+    let result = match reply {
+        Reply::Text(text) => send_telegram_message(&text).await,
+        Reply::Photo { png, caption } => telegram_outbox::send_photo(png, &caption),
+        Reply::Confirm {
+            preview,
+            pair,
+            order,
+        } => {
+            let id = pending_trades.add(pair, order, user_id);
+            send_telegram_confirmation(&preview, id).await
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("⚠️ Failed to send the reply: {}", e);
     }
-
-    Ok(())
 }
 
-pub async fn generate_market_status(left_asset: &str, right_asset: &str, sell_percentage: f64) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    use crate::log_manager::{load_trade_log, read_log};
-
-    let sol_holding = read_log(&format!(
-        "logs/solana/pair_{}_{}_value.txt",
-        left_asset, right_asset
-    ))?;
+/// Confirm / Cancel of a manual trade preview
+async fn handle_trade_button(
+    query: &CallbackQuery,
+    authorizer: &Authorizer,
+    pending_trades: &mut PendingTrades,
+) {
+    let Some((action, id)) = query
+        .data
+        .as_deref()
+        .and_then(|data| data.split_once(':'))
+        .and_then(|(action, id)| Some((action, id.parse::<u64>().ok()?)))
+    else {
+        return;
+    };
+    let message_id = query.message.as_ref().map(|msg| msg.message_id);
+    let Some(trade) = pending_trades.get(id) else {
+        if let Some(message_id) = message_id {
+            remove_inline_keyboard(message_id).await.ok();
+        }
+        answer_callback_query(&query.id, "⌛ Expired, send the command again")
+            .await
+            .ok();
+        return;
+    };
 
-    let trade_log = load_trade_log(&format!(
-        "logs/solana/pair_{}_{}_trade_history.json",
-        left_asset, right_asset
-    ))?;
+    let text = format!("{} {} [{}]", trade.order.label(), trade.pair.name(), action);
+    if let Err(denied) =
+        authorizer.authorize(Some(query.from.id), query.from.username.as_deref(), &text)
+    {
+        answer_callback_query(&query.id, &denied).await.ok();
+        return;
+    }
+    if trade.user_id.is_some_and(|user_id| user_id != query.from.id) {
+        answer_callback_query(&query.id, "⛔ Only the requester can confirm this trade")
+            .await
+            .ok();
+        return;
+    }
+    let Some(trade) = pending_trades.remove(id) else {
+        return;
+    };
+    if let Some(message_id) = message_id {
+        remove_inline_keyboard(message_id).await.ok();
+    }
 
-    let open_trades: Vec<&_> =
-        if let Some(last_sell_idx) = trade_log.iter().rposition(|t| t.trade_type == "sell") {
-            trade_log.iter().skip(last_sell_idx + 1).collect()
-        } else {
-            trade_log.iter().collect()
+    if action != "confirm" {
+        answer_callback_query(&query.id, "Cancelled").await.ok();
+        send_telegram_message(&format!(
+            "❎ `{}` cancelled [{}]",
+            trade.order.label(),
+            trade.pair.name()
+        ))
+        .await
+        .ok();
+        return;
+    }
+    answer_callback_query(&query.id, "Running…").await.ok();
+    // Waiting for the strategy to pause must not block the listener
+    tokio::spawn(async move {
+        let message = match execute(&trade.pair, trade.order).await {
+            Ok(report) => report,
+            Err(e) => format!(
+                "❌ `{}` failed [{}]: {e}",
+                trade.order.label(),
+                trade.pair.name()
+            ),
         };
+        send_telegram_message(&message).await.ok();
+    });
+}
 
-    let paid_usdc: f64 = open_trades.iter().map(|trade| trade.amount_token_a).sum();
-
-    let amount_lamports = (sol_holding * 1_000_000_000.0) as u64;
-    let quote_url = format!(
-        "https://quote-api.jup.ag/v6/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-        left_asset, right_asset, amount_lamports, 50
-    );
-
-    let client = reqwest::Client::new();
-    let quote_resp = client.get(&quote_url).send().await?;
-    let quote_json: serde_json::Value = quote_resp.json().await?;
-    let out_amount_str = quote_json["outAmount"]
-        .as_str()
-        .ok_or("Missing outAmount")?;
-    let usdc_received = out_amount_str.parse::<f64>()? / 1_000_000.0;
-
-    let target_return = paid_usdc * (1.0 + sell_percentage / 100.0);
-    let price_change = if paid_usdc > 0.0 {
-        100.0 * (usdc_received / paid_usdc - 1.0)
-    } else {
-        0.0
-    };
+/// Sends a manual trade preview with Confirm / Cancel buttons carrying the pending trade id
+pub async fn send_telegram_confirmation(message: &str, id: u64) -> BotResult<()> {
+    let keyboard = serde_json::json!({
+        "inline_keyboard": [[
+            { "text": "✅ Confirm", "callback_data": format!("confirm:{}", id) },
+            { "text": "❌ Cancel", "callback_data": format!("cancel:{}", id) },
+        ]]
+    });
+    telegram_outbox::send_text(message, Some(keyboard.to_string()))
+}
+
+async fn answer_callback_query(callback_query_id: &str, text: &str) -> BotResult<()> {
+    telegram_outbox::call(
+        "answerCallbackQuery",
+        &[
+            ("callback_query_id", callback_query_id.to_string()),
+            ("text", text.to_string()),
+        ],
+        false,
+    )
+}
+
+/// Removes the buttons so a preview cannot be pressed twice
+async fn remove_inline_keyboard(message_id: i64) -> BotResult<()> {
+    telegram_outbox::call(
+        "editMessageReplyMarkup",
+        &[
+            ("message_id", message_id.to_string()),
+            ("reply_markup", r#"{"inline_keyboard":[]}"#.to_string()),
+        ],
+        true,
+    )
+}
 
-    Ok(format!(
-        "🔁 Holding: {:.6} SOL →\n\
-         🔁 Would return {:.6} USDC for selling {:.6} SOL\n\
-         🎯 Need at least {:.6} USDC to sell for profit (+{:.1}%)\n\
-         📉 Price is at {:+.2}%",
-        sol_holding, usdc_received, sol_holding, target_return, sell_percentage, price_change
-    ))
+/// Queued and sent in the background (rate limited, retried, escaped for MarkdownV2)
+pub async fn send_telegram_message(message: &str) -> BotResult<()> {
+    telegram_outbox::send_text(message, None)
 }
 
 // End of Telegram API
@@ -1,13 +1,37 @@
-use std::{time::Duration};
-
 use log_manager::telegram_command_listener;
-use utils::run_jupiter_bot;
 // use osmosis_strategy_start::osmo_bot_start;
 
+mod backtest;
+mod cli;
+mod commands;
+mod config;
+mod dca_strategy;
+mod digest;
+mod error;
+mod event_log;
+mod grid_strategy;
 mod jupiter_strategy_start;
 mod log_manager;
+mod manual_trade;
+mod market_feed;
 mod osmosis_strategy_start;
+mod portfolio;
+mod price_chart;
 mod market_risk_analyzer;
+mod notifier;
+mod reconcile;
+mod risk_limits;
+mod rpc_pool;
+mod runtime_params;
+mod state_store;
+mod strategy;
+mod supervisor;
+mod swap_venue;
+mod telegram_auth;
+mod telegram_outbox;
+mod telegram_webhook;
+mod token_registry;
+mod trade_stats;
 mod utils;
 
 // #[tokio::main]
@@ -96,40 +120,141 @@ mod utils;
 //     }
 // }
 
-use tokio::sync::Mutex;
 use std::sync::Arc;
+use config::BotConfig;
+use supervisor::Supervisor;
 
 #[tokio::main]
 async fn main() {
-    
-    let left_asset = "So11111111111111111111111111111111111111112";
-    let right_asset = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
-
-    let sell_percentage: f64 = 2.3;
-    let dca_recover_percentage: f64 = 3.5;
-    let r_factor: f64 = 0.5;
-
-    println!("Starting Jupiter Bot [{left_asset}/{right_asset}] ...");
-    println!("Running strategy with parameters:");
-    println!("- sell_percentage: {}%", sell_percentage);
-    println!("- dca_recover_percentage: {}%", dca_recover_percentage);
-    println!("- r_factor: {}%", r_factor);
-
-    let trading_flag = Arc::new(Mutex::new(false));
-
-    // Spawn Telegram command listener
-    let listener_flag = trading_flag.clone();
-    tokio::spawn(async move {
-        telegram_command_listener( left_asset, right_asset, sell_percentage, listener_flag).await;
-    });
-
-    loop {
-        if *trading_flag.lock().await {
-            let flag_clone = trading_flag.clone();
-            run_jupiter_bot(left_asset, right_asset, sell_percentage, dca_recover_percentage, r_factor,flag_clone).await;
-            *trading_flag.lock().await = false;
+    let config = match BotConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    for pair in &config.pairs {
+        if let Err(e) = std::fs::create_dir_all(pair.state_dir()) {
+            eprintln!("❌ Failed to create log dir {}: {}", pair.state_dir(), e);
+            std::process::exit(1);
         }
+    }
+
+    // `tradeRS-bot backtest <candles.csv> [pair] [starting_usdc]`
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("backtest") {
+        run_backtest_cli(&config, &args[2..]).await;
+        return;
+    }
+
+    // Mint decimals are shared by all pairs, cached next to the first pair's logs
+    token_registry::init_token_registry(
+        rpc_pool::rpc_pool_for(&config.pairs[0]),
+        &config.pairs[0].log_dir,
+    );
+
+    for pair in &config.pairs {
+        println!(
+            "Starting Jupiter Bot {} [{}/{}]{} ...",
+            pair.pair_name(),
+            pair.left_asset,
+            pair.right_asset,
+            if pair.is_paper() { " (PAPER)" } else { "" }
+        );
+        println!("Running strategy with parameters:");
+        println!("- sell_percentage: {}%", pair.sell_percentage);
+        println!("- dca_recover_percentage: {}%", pair.dca_recover_percentage);
+        println!("- r_factor: {}%", pair.r_factor);
+    }
+
+    // The wallet every live Jupiter pair signs with
+    if config
+        .pairs
+        .iter()
+        .any(|pair| !pair.is_paper() && matches!(pair.venue, config::VenueConfig::Jupiter))
+    {
+        match swap_venue::wallet_address() {
+            Ok(address) => println!("✅ Connected Wallet Address: {:?}", address),
+            Err(e) => eprintln!("❌ {}", e),
+        }
+    }
+
+    notifier::init_notifiers(&config.notifiers);
+    // Drawdown limit over the summed equity of the pairs
+    portfolio::init_portfolios(&config);
+
+    // Compare recorded holdings with the wallet without holding up the listener
+    tokio::spawn(reconcile::reconcile_at_startup(config.pairs.clone()));
 
-        tokio::time::sleep(Duration::from_secs(2)).await;
+    // Daily / weekly summaries, sent even when Telegram commands are disabled
+    tokio::spawn(digest::run_digests(config.digest.clone(), config.pairs.clone()));
+
+    // Every command is checked against the user roles and audit-logged
+    let authorizer = telegram_auth::Authorizer::new(
+        &config.telegram,
+        &format!("{}/telegram_audit.log", config.pairs[0].log_dir),
+    );
+
+    // Handled update ids survive restarts so no command runs twice
+    let offset_path = format!("{}/telegram_offset.json", config.pairs[0].log_dir);
+
+    // One task per pair, stopped until /start_trading (or started right away with autostart)
+    let supervisor = Arc::new(Supervisor::start(config.pairs).await);
+    let runner = commands::CommandRunner::new(supervisor);
+
+    // Local console, dispatching through the same commands as Telegram
+    tokio::spawn(cli::run_cli(runner.clone()));
+
+    // Telegram command listener drives the supervisor
+    if std::env::var("TELEGRAM_HTTP_API").is_ok() && std::env::var("TELEGRAM_CHAT_ID").is_ok() {
+        telegram_command_listener(runner, authorizer, &config.telegram, &offset_path).await;
+    } else {
+        println!("⚠️ TELEGRAM_HTTP_API / TELEGRAM_CHAT_ID not set: Telegram commands are disabled");
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+async fn run_backtest_cli(config: &BotConfig, args: &[String]) {
+    let Some(candles_path) = args.first() else {
+        eprintln!("Usage: tradeRS-bot backtest <candles.csv> [pair] [starting_usdc]");
+        std::process::exit(1);
+    };
+    let pair = match args.get(1) {
+        Some(name) => config.pairs.iter().find(|p| p.pair_name().eq_ignore_ascii_case(name)),
+        None => config.pairs.first(),
+    };
+    let Some(pair) = pair else {
+        eprintln!("❌ Unknown pair {:?}", args.get(1));
+        std::process::exit(1);
+    };
+    let starting_usdc = match args.get(2).map(|v| v.parse::<f64>()) {
+        None => 1000.0,
+        Some(Ok(v)) if v > 0.0 => v,
+        Some(_) => {
+            eprintln!("❌ starting_usdc must be a positive number");
+            std::process::exit(1);
+        }
+    };
+
+    let max_drawdown_percentage = config.risk.max_drawdown_percentage;
+    match backtest::run_backtest(pair, max_drawdown_percentage, candles_path, starting_usdc).await {
+        Ok(report) => {
+            println!("\n🧾 Trade log:");
+            for trade in &report.trades {
+                println!(
+                    "{} | {:<4} | {:.6} → {:.6} | DCA level {}",
+                    trade.time,
+                    trade.trade_type,
+                    trade.amount_token_a,
+                    trade.amount_token_b,
+                    trade.dca_level.unwrap_or(0)
+                );
+            }
+            println!("\n{}", report.summary(&pair.pair_name()));
+        }
+        Err(e) => {
+            eprintln!("❌ Backtest failed: {}", e);
+            std::process::exit(1);
+        }
     }
 }
@@ -0,0 +1,223 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config::StrategyConfig;
+use crate::dca_strategy::plan_ladder;
+use crate::error::{BotError, BotResult};
+use crate::runtime_params::effective_config;
+use crate::state_store::{PairState, StateStore};
+use crate::supervisor::PairHandle;
+use crate::swap_venue::build_venue;
+use crate::token_registry::token_registry;
+use crate::trade_stats::paper_tag;
+
+/// A preview older than this must be requested again (the quote is stale)
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A trade requested from Telegram
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManualOrder {
+    Buy(f64),  // Amount of `right_asset` to spend (e.g. USDC)
+    Sell(f64), // Percentage of the recorded holding
+    PanicSell, // The whole recorded holding, automatic trading stays stopped
+}
+
+impl ManualOrder {
+    /// Parses `/buy 50`, `/sell 50%` or `/panic_sell`, returning the optional pair argument too
+    pub fn parse<'a>(command: &str, args: &[&'a str]) -> Result<(Self, Option<&'a str>), String> {
+        match command {
+            "/panic_sell" => Ok((ManualOrder::PanicSell, args.first().copied())),
+            "/buy" => match args.first().and_then(|a| a.parse::<f64>().ok()) {
+                Some(amount) if amount > 0.0 && amount.is_finite() => {
+                    Ok((ManualOrder::Buy(amount), args.get(1).copied()))
+                }
+                _ => Err("Usage: /buy <amount> [pair], e.g. /buy 50".to_string()),
+            },
+            "/sell" => match args
+                .first()
+                .and_then(|a| a.trim_end_matches('%').parse::<f64>().ok())
+            {
+                Some(percentage) if percentage > 0.0 && percentage <= 100.0 => {
+                    Ok((ManualOrder::Sell(percentage), args.get(1).copied()))
+                }
+                _ => Err("Usage: /sell <percentage>% [pair], e.g. /sell 50%".to_string()),
+            },
+            _ => Err(format!("Unknown trade command {}", command)),
+        }
+    }
+
+    /// The command as typed, e.g. for the audit log
+    pub fn label(&self) -> String {
+        match self {
+            ManualOrder::Buy(amount) => format!("/buy {}", amount),
+            ManualOrder::Sell(percentage) => format!("/sell {}%", percentage),
+            ManualOrder::PanicSell => "/panic_sell".to_string(),
+        }
+    }
+
+    /// Share of the holding sold, `None` for a buy
+    fn sell_fraction(&self) -> Option<f64> {
+        match self {
+            ManualOrder::Buy(_) => None,
+            ManualOrder::Sell(percentage) => Some(percentage / 100.0),
+            ManualOrder::PanicSell => Some(1.0),
+        }
+    }
+
+    /// Input mint, output mint and input amount of the swap
+    fn swap_args<'a>(
+        &self,
+        config: &'a StrategyConfig,
+        state: &PairState,
+    ) -> BotResult<(&'a str, &'a str, f64)> {
+        match (self, self.sell_fraction()) {
+            (ManualOrder::Buy(amount), _) => Ok((&config.right_asset, &config.left_asset, *amount)),
+            (_, Some(fraction)) if state.holding > 0.0 => Ok((
+                &config.left_asset,
+                &config.right_asset,
+                state.holding * fraction,
+            )),
+            _ => Err(BotError::InsufficientFunds(format!(
+                "no recorded position to sell on {}",
+                config.pair_name()
+            ))),
+        }
+    }
+}
+
+/// Quotes the order and describes what confirming it will do
+pub async fn preview(config: &StrategyConfig, order: ManualOrder) -> BotResult<String> {
+    let config = effective_config(config)?;
+    let state = StateStore::open(&config)?.load()?;
+    let (input, output, amount) = order.swap_args(&config, &state)?;
+    let quote = build_venue(&config)?
+        .quote(input, output, amount, config.slippage_bps_max)
+        .await?;
+    let input_symbol = token_registry().symbol(input).await;
+    let output_symbol = token_registry().symbol(output).await;
+    let paper = paper_tag(&config);
+
+    let mut lines = vec![match order {
+        ManualOrder::Buy(_) => format!("🛒 *Manual buy* [{}]{}", config.pair_name(), paper),
+        ManualOrder::Sell(percentage) => format!(
+            "💸 *Manual sell {}%* [{}]{}",
+            percentage,
+            config.pair_name(),
+            paper
+        ),
+        ManualOrder::PanicSell => format!("🚨 *Panic sell* [{}]{}", config.pair_name(), paper),
+    }];
+    lines.push(format!(
+        "Swap {:.6} {} → ≈{:.6} {}",
+        amount, input_symbol, quote.out_amount, output_symbol
+    ));
+    if let Some(fraction) = order.sell_fraction() {
+        lines.push(format!(
+            "PnL on the sold part: {:+.2} {}",
+            quote.out_amount - state.open_cost() * fraction,
+            output_symbol
+        ));
+    }
+    if order == ManualOrder::PanicSell {
+        lines.push("Automatic trading stays stopped afterwards.".to_string());
+    }
+    lines.push(format!("Confirm within {}s.", CONFIRM_TIMEOUT.as_secs()));
+    Ok(lines.join("\n"))
+}
+
+/// Pauses the strategy of the pair, runs the order and records it like a bot trade
+pub async fn execute(pair: &PairHandle, order: ManualOrder) -> BotResult<String> {
+    let was_trading = std::mem::replace(&mut *pair.trading_flag.lock().await, false);
+    // Waits for the current strategy iteration to finish
+    let busy = pair.busy.lock().await;
+    let result = run_order(&pair.config, order).await;
+    if was_trading && order != ManualOrder::PanicSell {
+        *pair.trading_flag.lock().await = true;
+    }
+    drop(busy);
+    result
+}
+
+async fn run_order(config: &StrategyConfig, order: ManualOrder) -> BotResult<String> {
+    let config = effective_config(config)?;
+    let store = StateStore::open(&config)?;
+    let mut state = store.load()?;
+    let (input, output, amount) = order.swap_args(&config, &state)?;
+    let fill = build_venue(&config)?
+        .swap(input, output, amount, config.slippage_bps_max)
+        .await?;
+
+    let now = Utc::now();
+    match order.sell_fraction() {
+        None => {
+            let (dca_level, opens) = (state.dca_level, state.holding == 0.0);
+            state.record_buy(amount, fill.out_amount, dca_level, now);
+            if opens {
+                state.ladder = plan_ladder(&config, amount, fill.out_amount);
+            }
+        }
+        Some(fraction) if fraction < 1.0 => state.record_partial_sell(amount, fill.out_amount, now),
+        Some(_) => state.record_sell(amount, fill.out_amount, now),
+    }
+    store.save(&state)?;
+
+    let input_symbol = token_registry().symbol(input).await;
+    let output_symbol = token_registry().symbol(output).await;
+    Ok(format!(
+        "✅ `{}` done [{}]\nSwapped {:.6} {} → {:.6} {} in tx:\n`{}`\nHolding now {:.6}",
+        order.label(),
+        config.pair_name(),
+        amount,
+        input_symbol,
+        fill.out_amount,
+        output_symbol,
+        fill.tx_signature,
+        state.holding
+    ))
+}
+
+/// A previewed order waiting for its Confirm / Cancel button
+pub struct PendingTrade {
+    pub pair: Arc<PairHandle>,
+    pub order: ManualOrder,
+    pub user_id: Option<i64>, // Only the requester can confirm
+    created: Instant,
+}
+
+/// Previewed orders by the id carried in the button callback data
+#[derive(Default)]
+pub struct PendingTrades {
+    next_id: u64,
+    trades: HashMap<u64, PendingTrade>,
+}
+
+impl PendingTrades {
+    pub fn add(&mut self, pair: Arc<PairHandle>, order: ManualOrder, user_id: Option<i64>) -> u64 {
+        self.trades
+            .retain(|_, trade| trade.created.elapsed() < CONFIRM_TIMEOUT);
+        self.next_id += 1;
+        self.trades.insert(
+            self.next_id,
+            PendingTrade {
+                pair,
+                order,
+                user_id,
+                created: Instant::now(),
+            },
+        );
+        self.next_id
+    }
+
+    /// The pending order, unless it expired
+    pub fn get(&self, id: u64) -> Option<&PendingTrade> {
+        self.trades
+            .get(&id)
+            .filter(|trade| trade.created.elapsed() < CONFIRM_TIMEOUT)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<PendingTrade> {
+        self.trades.remove(&id)
+    }
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+use crate::market_risk_analyzer::fetch_and_log_binance_history;
+
+/// Source of time and price history for the strategy loop (live or replayed)
+#[async_trait]
+pub trait MarketFeed: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Refreshes the OHLCV price log used by the market risk check
+    async fn refresh_history(&self, path: &str, symbol: &str) -> Result<(), String>;
+
+    /// Pause at the end of a loop iteration, before looking at the market again
+    async fn tick(&self, duration: Duration);
+
+    /// Pause between two attempts of the same swap
+    async fn retry_delay(&self, duration: Duration);
+}
+
+/// Wall clock and Binance klines
+pub struct LiveFeed;
+
+#[async_trait]
+impl MarketFeed for LiveFeed {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn refresh_history(&self, path: &str, symbol: &str) -> Result<(), String> {
+        fetch_and_log_binance_history(path, symbol, "4").await
+    }
+
+    async fn tick(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+
+    async fn retry_delay(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
@@ -4,7 +4,79 @@ use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 
 // === CONFIG ===
-const MAX_HISTORY_LINES: usize = 1000;
+pub const MAX_HISTORY_LINES: usize = 1000;
+
+/// One Binance kline as stored in the price log (`open_time,open,high,low,close,volume`)
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub open_time: i64, // Milliseconds since epoch
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl Candle {
+    fn from_kline(kline: &Value) -> Option<Self> {
+        let field = |idx: usize| kline.get(idx)?.as_str()?.parse::<f64>().ok();
+        Some(Self {
+            open_time: kline.get(0)?.as_i64()?,
+            open: field(1)?,
+            high: field(2)?,
+            low: field(3)?,
+            close: field(4)?,
+            volume: field(5)?,
+        })
+    }
+
+    /// Parses a full OHLCV line; returns `None` for legacy close-only lines
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        if fields.len() != 6 {
+            return None;
+        }
+        Some(Self {
+            open_time: fields[0].parse().ok()?,
+            open: fields[1].parse().ok()?,
+            high: fields[2].parse().ok()?,
+            low: fields[3].parse().ok()?,
+            close: fields[4].parse().ok()?,
+            volume: fields[5].parse().ok()?,
+        })
+    }
+
+    pub fn to_line(&self) -> String {
+        format!(
+            "{},{:.6},{:.6},{:.6},{:.6},{:.6}",
+            self.open_time, self.open, self.high, self.low, self.close, self.volume
+        )
+    }
+}
+
+/// Reads the close price of a price log line (OHLCV or legacy close-only format)
+pub fn parse_close(line: &str) -> Option<f64> {
+    match Candle::parse_line(line) {
+        Some(candle) => Some(candle.close),
+        None => line.trim().parse::<f64>().ok(),
+    }
+}
+
+/// Loads all the OHLCV candles of a price log file
+pub fn load_candles(path: &str) -> Result<Vec<Candle>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read: {}", e))?;
+    let candles: Vec<Candle> = contents.lines().filter_map(Candle::parse_line).collect();
+    if candles.is_empty() {
+        return Err(format!("No OHLCV candles found in {}", path));
+    }
+    Ok(candles)
+}
+
+/// Overwrites a price log with the given candles
+pub fn write_candles(path: &str, candles: &[Candle]) -> Result<(), String> {
+    let content: String = candles.iter().map(|c| c.to_line() + "\n").collect();
+    fs::write(path, content).map_err(|e| format!("Failed to write price log: {}", e))
+}
 
 /// Struct to analyze how many times a price bucket has been touched.
 pub struct PriceTouchAnalyzer {
@@ -12,7 +84,7 @@ pub struct PriceTouchAnalyzer {
     pub bucket_size: f64,
 }
 
-/// Fetches recent price history from Binance and overwrites the local log file
+/// Fetches recent klines from Binance and overwrites the local log file (OHLCV)
 pub async fn fetch_and_log_binance_history(
     path: &str,
     symbol: &str,
@@ -36,21 +108,13 @@ pub async fn fetch_and_log_binance_history(
         .as_array()
         .ok_or("Unexpected JSON format from Binance")?;
 
-    let mut lines = vec![];
-    for candle in arr {
-        if let Some(close_str) = candle.get(4).and_then(|v| v.as_str()) {
-            if let Ok(close) = close_str.parse::<f64>() {
-                lines.push(format!("{:.6}", close));
-            }
-        }
-    }
+    let candles: Vec<Candle> = arr.iter().filter_map(Candle::from_kline).collect();
 
-    if lines.is_empty() {
+    if candles.is_empty() {
         return Err("No valid price data fetched.".into());
     }
 
-    let content = lines.join("\n") + "\n";
-    fs::write(path, content).map_err(|e| format!("Failed to write price log: {}", e))
+    write_candles(path, &candles)
 }
 
 /// Appends the current price to a log file
@@ -85,25 +149,22 @@ pub fn fetch_current_binance_price_from_log(path: &str) -> Result<f64, String> {
 
     let last_line = reader
         .lines()
-        .filter_map(|l| l.ok())
+        .map_while(Result::ok)
         .filter(|l| !l.trim().is_empty())
         .last()
         .ok_or("Price log is empty")?;
 
-    last_line
-        .trim()
-        .parse::<f64>()
-        .map_err(|e| format!("Failed to parse last price: {}", e))
+    parse_close(&last_line).ok_or_else(|| format!("Failed to parse last price: {}", last_line))
 }
 
 impl PriceTouchAnalyzer {
-    /// Load price history from a file (one price per line)
+    /// Load price history from a file (one candle or price per line)
     pub fn from_file(path: &str, bucket_size: f64) -> Result<Self, String> {
         let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read: {}", e))?;
         let mut prices = vec![];
 
         for line in contents.lines() {
-            if let Ok(p) = line.trim().parse::<f64>() {
+            if let Some(p) = parse_close(line) {
                 prices.push(p);
             }
         }
@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::OnceLock;
+
+use crate::config::{NotifierConfig, NotifierKind, Severity};
+use crate::error::BotResult;
+use crate::log_manager::send_telegram_message;
+
+/// Notifiers from the config, shared by every pair
+static NOTIFIERS: OnceLock<Vec<FilteredNotifier>> = OnceLock::new();
+
+/// A destination for alerts and trade notifications
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, severity: Severity, message: &str) -> BotResult<()>;
+}
+
+struct FilteredNotifier {
+    notifier: Box<dyn Notifier>,
+    min_severity: Severity,
+}
+
+pub struct TelegramNotifier;
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    async fn send(&self, _severity: Severity, message: &str) -> BotResult<()> {
+        send_telegram_message(message).await
+    }
+}
+
+/// Any JSON body POSTed to a URL (generic webhook, Discord, Slack)
+pub struct WebhookNotifier {
+    kind: &'static str,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(kind: &'static str, url: &str) -> Self {
+        Self {
+            kind,
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Messages are written for Telegram Markdown, these destinations get the plain text
+    fn body(&self, severity: Severity, message: &str) -> serde_json::Value {
+        let message = plain(message);
+        match self.kind {
+            "discord" => serde_json::json!({ "content": message }),
+            "slack" => serde_json::json!({ "text": message }),
+            _ => serde_json::json!({
+                "severity": severity,
+                "message": message,
+                "time": chrono::Utc::now().to_rfc3339(),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        self.kind
+    }
+
+    async fn send(&self, severity: Severity, message: &str) -> BotResult<()> {
+        self.client
+            .post(&self.url)
+            .json(&self.body(severity, message))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Prints to stdout, or appends to a file when a path is given
+pub struct LogNotifier {
+    path: Option<String>,
+}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    fn name(&self) -> &'static str {
+        if self.path.is_some() { "file" } else { "stdout" }
+    }
+
+    async fn send(&self, severity: Severity, message: &str) -> BotResult<()> {
+        let line = format!(
+            "{} [{:?}] {}",
+            chrono::Utc::now().to_rfc3339(),
+            severity,
+            plain(message).replace('\n', " | ")
+        );
+        match &self.path {
+            None => println!("📣 {}", line),
+            Some(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drops the Telegram Markdown
+pub fn plain(text: &str) -> String {
+    text.replace("\\_", "_").replace(['*', '`'], "")
+}
+
+fn build_notifier(kind: &NotifierKind) -> Box<dyn Notifier> {
+    match kind {
+        NotifierKind::Telegram => Box::new(TelegramNotifier),
+        NotifierKind::Webhook { url } => Box::new(WebhookNotifier::new("webhook", url)),
+        NotifierKind::Discord { url } => Box::new(WebhookNotifier::new("discord", url)),
+        NotifierKind::Slack { url } => Box::new(WebhookNotifier::new("slack", url)),
+        NotifierKind::Stdout => Box::new(LogNotifier { path: None }),
+        NotifierKind::File { path } => Box::new(LogNotifier {
+            path: Some(path.clone()),
+        }),
+    }
+}
+
+/// Sets up the configured notifiers (Telegram alone when none is configured)
+pub fn init_notifiers(configs: &[NotifierConfig]) {
+    let notifiers = if configs.is_empty() {
+        vec![FilteredNotifier {
+            notifier: Box::new(TelegramNotifier),
+            min_severity: Severity::Info,
+        }]
+    } else {
+        configs
+            .iter()
+            .map(|config| FilteredNotifier {
+                notifier: build_notifier(&config.kind),
+                min_severity: config.min_severity,
+            })
+            .collect()
+    };
+    let _ = NOTIFIERS.set(notifiers);
+}
+
+/// Sends to every notifier that accepts `severity`; failures are logged, never returned
+pub async fn notify(severity: Severity, message: &str) {
+    let Some(notifiers) = NOTIFIERS.get() else {
+        println!("📣 [{:?}] {}", severity, message);
+        return;
+    };
+    for filtered in notifiers
+        .iter()
+        .filter(|filtered| severity >= filtered.min_severity)
+    {
+        if let Err(e) = filtered.notifier.send(severity, message).await {
+            eprintln!(
+                "⚠️ {} notification failed: {}",
+                filtered.notifier.name(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhooks_get_the_message_without_telegram_markdown() {
+        let message = "✅ *SOL/USDC*: sold at `150.00` (pair\\_state.json)";
+        let plain = "✅ SOL/USDC: sold at 150.00 (pair_state.json)";
+        let discord = WebhookNotifier::new("discord", "http://localhost");
+        assert_eq!(discord.body(Severity::Info, message)["content"], plain);
+        let slack = WebhookNotifier::new("slack", "http://localhost");
+        assert_eq!(slack.body(Severity::Info, message)["text"], plain);
+        let webhook = WebhookNotifier::new("webhook", "http://localhost");
+        assert_eq!(webhook.body(Severity::Info, message)["message"], plain);
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::{BotConfig, StrategyConfig};
+use crate::error::BotResult;
+use crate::state_store::write_atomic;
+
+/// One portfolio per state dir, so live and paper pairs are measured apart
+static PORTFOLIOS: OnceLock<Vec<Portfolio>> = OnceLock::new();
+
+/// Saved next to the pair states, so a restart keeps the peak, a trip and the last equities
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PortfolioState {
+    #[serde(default)]
+    equities: HashMap<String, f64>, // Last equity reported by each pair
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    equity_peak: Option<f64>, // Highest summed equity of the pairs
+    #[serde(default)]
+    trips: u64, // Drawdown trips so far, so each running pair stops once per trip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tripped: Option<String>, // Reason of the last trip, until /resume
+}
+
+/// Bot-wide drawdown breaker: the equity of every pair summed and measured from one peak.
+/// A stopped pair counts with the last equity it reported.
+pub struct Portfolio {
+    path: String,
+    max_drawdown_percentage: f64,
+    state: Mutex<PortfolioState>,
+}
+
+impl Portfolio {
+    pub fn new(path: &str, max_drawdown_percentage: f64, pairs: &[String]) -> Self {
+        let mut state: PortfolioState = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        // Pairs removed from the config no longer count, so the old peak no longer compares
+        let reported = state.equities.len();
+        state.equities.retain(|pair, _| pairs.contains(pair));
+        if state.equities.len() < reported {
+            state.equity_peak = None;
+        }
+        Self {
+            path: path.to_string(),
+            max_drawdown_percentage,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Trips seen so far; a pair starts from here and only stops for later trips
+    pub fn trips(&self) -> u64 {
+        self.state.lock().unwrap().trips
+    }
+
+    /// Records the equity of a pair, then raises the peak or trips on the summed equity
+    pub fn report(&self, pair: &str, equity: f64) -> BotResult<()> {
+        let mut state = self.state.lock().unwrap();
+        state.equities.insert(pair.to_string(), equity);
+        let total: f64 = state.equities.values().sum();
+        match state.equity_peak {
+            _ if state.tripped.is_some() => {}
+            Some(peak) if total <= peak * (1.0 - self.max_drawdown_percentage / 100.0) => {
+                state.trips += 1;
+                state.tripped = Some(format!(
+                    "Max drawdown: portfolio equity {:.2} is {:.2}% below its peak {:.2} (limit {}%)",
+                    total,
+                    100.0 * (1.0 - total / peak),
+                    peak,
+                    self.max_drawdown_percentage
+                ));
+            }
+            Some(peak) if total <= peak => {}
+            _ => state.equity_peak = Some(total),
+        }
+        write_atomic(&self.path, &serde_json::to_string_pretty(&*state)?)
+    }
+
+    /// Reason to stop a pair that has seen `seen_trips` trips: a trip it missed, or one not resumed yet
+    pub fn tripped_since(&self, seen_trips: &mut u64) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        if state.trips > *seen_trips {
+            *seen_trips = state.trips;
+            return Some(
+                state
+                    .tripped
+                    .clone()
+                    .unwrap_or_else(|| "Max drawdown: the portfolio limit tripped".to_string()),
+            );
+        }
+        state.tripped.clone()
+    }
+
+    /// Clears a trip for /resume; the drawdown is measured again from the current equity
+    pub fn resume(&self) -> BotResult<()> {
+        let mut state = self.state.lock().unwrap();
+        state.tripped = None;
+        state.equity_peak = None;
+        write_atomic(&self.path, &serde_json::to_string_pretty(&*state)?)
+    }
+}
+
+/// Sets up the drawdown breaker of `[risk]`, if any
+pub fn init_portfolios(config: &BotConfig) {
+    let Some(pct) = config.risk.max_drawdown_percentage else {
+        return;
+    };
+    let mut state_dirs: Vec<String> = config.pairs.iter().map(|p| p.state_dir()).collect();
+    state_dirs.sort();
+    state_dirs.dedup();
+    let portfolios = state_dirs
+        .into_iter()
+        .map(|state_dir| {
+            let pairs: Vec<String> = config
+                .pairs
+                .iter()
+                .filter(|p| p.state_dir() == state_dir)
+                .map(|p| p.pair_name())
+                .collect();
+            Portfolio::new(&format!("{}/portfolio_state.json", state_dir), pct, &pairs)
+        })
+        .collect();
+    let _ = PORTFOLIOS.set(portfolios);
+}
+
+/// The portfolio a pair belongs to, when the drawdown breaker is on
+pub fn portfolio_for(config: &StrategyConfig) -> Option<&'static Portfolio> {
+    let path = format!("{}/portfolio_state.json", config.state_dir());
+    PORTFOLIOS.get()?.iter().find(|p| p.path == path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAIRS: [&str; 2] = ["a", "b"];
+
+    fn portfolio(test: &str) -> Portfolio {
+        let path =
+            std::env::temp_dir().join(format!("tradeRS-bot-{}-{}.json", test, std::process::id()));
+        let _ = fs::remove_file(&path);
+        Portfolio::new(&path.display().to_string(), 10.0, &PAIRS.map(String::from))
+    }
+
+    fn peak(portfolio: &Portfolio) -> Option<f64> {
+        portfolio.state.lock().unwrap().equity_peak
+    }
+
+    #[test]
+    fn the_summed_equity_trips_every_pair_once() {
+        let portfolio = portfolio("portfolio-trip");
+        let (mut seen_a, mut seen_b) = (portfolio.trips(), portfolio.trips());
+
+        portfolio.report("a", 500.0).unwrap();
+        portfolio.report("b", 600.0).unwrap();
+        assert_eq!(peak(&portfolio), Some(1100.0));
+
+        // Pair a alone is down 20% but the portfolio only 9%
+        portfolio.report("a", 400.0).unwrap();
+        assert_eq!(portfolio.tripped_since(&mut seen_a), None);
+
+        portfolio.report("b", 580.0).unwrap();
+        let reason = portfolio.tripped_since(&mut seen_a).unwrap();
+        assert!(
+            reason.starts_with("Max drawdown: portfolio equity 980.00"),
+            "{reason}"
+        );
+
+        // Resuming before pair b looked again still stops it, but only once
+        portfolio.resume().unwrap();
+        assert_eq!(portfolio.tripped_since(&mut seen_a), None);
+        assert!(portfolio.tripped_since(&mut seen_b).is_some());
+        assert_eq!(portfolio.tripped_since(&mut seen_b), None);
+
+        // The peak starts again from the current equity
+        portfolio.report("a", 400.0).unwrap();
+        assert_eq!(peak(&portfolio), Some(980.0));
+        let _ = fs::remove_file(&portfolio.path);
+    }
+
+    #[test]
+    fn a_restart_keeps_the_equity_of_pairs_not_reported_yet() {
+        let portfolio = portfolio("portfolio-restart");
+        portfolio.report("a", 500.0).unwrap();
+        portfolio.report("b", 600.0).unwrap();
+
+        // Only pair a trades after the restart, pair b still counts
+        let reloaded = Portfolio::new(&portfolio.path, 10.0, &PAIRS.map(String::from));
+        reloaded.report("a", 450.0).unwrap();
+        assert_eq!(reloaded.tripped_since(&mut reloaded.trips()), None);
+        assert_eq!(peak(&reloaded), Some(1100.0));
+
+        // A pair dropped from the config no longer counts, the peak starts again
+        let reloaded = Portfolio::new(&portfolio.path, 10.0, &["a".to_string()]);
+        reloaded.report("a", 460.0).unwrap();
+        assert_eq!(reloaded.tripped_since(&mut 0), None);
+        assert_eq!(peak(&reloaded), Some(460.0));
+        let _ = fs::remove_file(&portfolio.path);
+    }
+}
@@ -0,0 +1,337 @@
+use chrono::{DateTime, Duration, Utc};
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use plotters::prelude::*;
+use plotters::style::{FontStyle, register_font};
+use std::sync::Once;
+
+use crate::config::StrategyConfig;
+use crate::error::{BotError, BotResult};
+use crate::market_risk_analyzer::load_candles;
+use crate::runtime_params::effective_config;
+use crate::state_store::{PairState, StateStore};
+use crate::trade_stats::trade_time;
+
+/// Window of `/chart` when none is given
+pub const DEFAULT_TIMEFRAME: &str = "3d";
+const CHART_SIZE: (u32, u32) = (1200, 700);
+
+/// Fonts are embedded so rendering needs nothing installed on the server
+static REGISTER_FONT: Once = Once::new();
+
+/// Longest window `/chart` accepts
+const MAX_TIMEFRAME_HOURS: i64 = 366 * 24;
+
+/// Parses a `/chart` window like `12h`, `3d` or `2w`, up to a year
+pub fn parse_timeframe(value: &str) -> Option<Duration> {
+    let value = value.to_lowercase();
+    let (idx, _) = value.char_indices().last()?;
+    let (count, unit) = value.split_at(idx);
+    let count: i64 = count.parse().ok().filter(|count| *count > 0)?;
+    let window = match unit {
+        "h" => Duration::try_hours(count),
+        "d" => Duration::try_days(count),
+        "w" => Duration::try_weeks(count),
+        _ => None,
+    }?;
+    (window.num_hours() <= MAX_TIMEFRAME_HOURS).then_some(window)
+}
+
+/// Average entry, take-profit and next DCA price of the open position
+struct PositionLevels {
+    entry: f64,
+    take_profit: f64,
+    next_dca: Option<f64>, // None once every ladder level is used
+}
+
+impl PositionLevels {
+    fn of(state: &PairState, config: &StrategyConfig) -> Option<Self> {
+        if state.holding <= 0.0 {
+            return None;
+        }
+        let entry = state.open_cost() / state.holding;
+        Some(Self {
+            entry,
+            take_profit: entry * (1.0 + config.sell_percentage / 100.0),
+            next_dca: match &state.ladder {
+                Some(ladder) => ladder
+                    .steps
+                    .get(state.dca_level as usize)
+                    .map(|step| step.trigger_price),
+                None => Some(entry * (1.0 - config.dca_recover_percentage / 100.0)),
+            },
+        })
+    }
+}
+
+/// Renders the Binance closes of the last `window` with our trades and position levels,
+/// returning the PNG and a caption
+pub async fn render_chart(
+    config: &StrategyConfig,
+    window: Duration,
+) -> BotResult<(Vec<u8>, String)> {
+    let config = effective_config(config)?;
+    let state = StateStore::open(&config)?.load()?;
+    tokio::task::spawn_blocking(move || draw_chart(&config, &state, window))
+        .await
+        .map_err(|e| BotError::StateIo(format!("Chart rendering panicked: {}", e)))?
+}
+
+fn draw_chart(
+    config: &StrategyConfig,
+    state: &PairState,
+    window: Duration,
+) -> BotResult<(Vec<u8>, String)> {
+    let render_error =
+        |e: &dyn std::fmt::Display| BotError::StateIo(format!("Failed to render chart: {}", e));
+    REGISTER_FONT.call_once(|| {
+        if register_font(
+            "sans-serif",
+            FontStyle::Normal,
+            epaint_default_fonts::UBUNTU_LIGHT,
+        )
+        .is_err()
+        {
+            eprintln!("⚠️ Failed to load the chart font");
+        }
+    });
+
+    let candles = load_candles(&config.binance_price_log_path()).map_err(|e| {
+        BotError::StateIo(format!(
+            "{} (the price log is filled while the pair trades)",
+            e
+        ))
+    })?;
+    let closes: Vec<(DateTime<Utc>, f64)> = candles
+        .iter()
+        .filter_map(|c| Some((DateTime::from_timestamp_millis(c.open_time)?, c.close)))
+        .collect();
+    // Ends at the last candle so a stale log still shows something
+    let end = closes
+        .last()
+        .map(|(time, _)| *time)
+        .unwrap_or_else(Utc::now);
+    let first = closes.first().map_or(end, |(time, _)| *time);
+    let start = end
+        .checked_sub_signed(window)
+        .map_or(first, |start| start.max(first));
+    let closes: Vec<(DateTime<Utc>, f64)> = closes
+        .into_iter()
+        .filter(|(time, _)| *time >= start)
+        .collect();
+
+    // Fill price of every trade in the window, in `right_asset` per `left_asset`
+    let mut buys = Vec::new();
+    let mut dca_buys = Vec::new();
+    let mut sells = Vec::new();
+    for trade in &state.trades {
+        let Ok(time) = trade_time(trade) else {
+            continue;
+        };
+        if time < start || time > end || trade.amount_token_a <= 0.0 || trade.amount_token_b <= 0.0
+        {
+            continue;
+        }
+        match trade.trade_type.as_str() {
+            "buy" if trade.dca_level.unwrap_or(0) > 0 => {
+                dca_buys.push((time, trade.amount_token_a / trade.amount_token_b))
+            }
+            "buy" => buys.push((time, trade.amount_token_a / trade.amount_token_b)),
+            "sell" | "partial_sell" => {
+                sells.push((time, trade.amount_token_b / trade.amount_token_a))
+            }
+            _ => {}
+        }
+    }
+    let levels = PositionLevels::of(state, config);
+
+    let prices = closes
+        .iter()
+        .chain(&buys)
+        .chain(&dca_buys)
+        .chain(&sells)
+        .map(|(_, price)| *price)
+        .chain(
+            levels
+                .iter()
+                .flat_map(|l| [Some(l.entry), Some(l.take_profit), l.next_dca])
+                .flatten(),
+        );
+    let (low, high) = prices.fold((f64::MAX, f64::MIN), |(low, high), p| {
+        (low.min(p), high.max(p))
+    });
+    if low > high {
+        return Err(BotError::StateIo(format!(
+            "No prices in the last {}h",
+            window.num_hours()
+        )));
+    }
+    let padding = ((high - low) * 0.05).max(high * 0.001);
+
+    // Rendered in memory: concurrent /chart requests never share a file
+    let mut pixels = vec![0u8; (CHART_SIZE.0 * CHART_SIZE.1 * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, CHART_SIZE).into_drawing_area();
+        root.fill(&WHITE).map_err(|e| render_error(&e))?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("{} ({})", config.pair_name(), config.binance_symbol),
+                ("sans-serif", 28),
+            )
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(80)
+            .build_cartesian_2d(start..end, (low - padding)..(high + padding))
+            .map_err(|e| render_error(&e))?;
+        chart
+            .configure_mesh()
+            .x_labels(8)
+            .x_label_formatter(&|time| time.format("%d %b %H:%M").to_string())
+            .y_label_formatter(&|price| format!("{:.4}", price))
+            .label_style(("sans-serif", 16))
+            .draw()
+            .map_err(|e| render_error(&e))?;
+
+        chart
+            .draw_series(LineSeries::new(closes, BLUE.stroke_width(2)))
+            .map_err(|e| render_error(&e))?
+            .label("Close")
+            .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLUE));
+
+        if let Some(levels) = &levels {
+            for (label, price, color) in [
+                ("Average entry", Some(levels.entry), BLACK),
+                ("Take profit", Some(levels.take_profit), GREEN),
+                ("Next DCA", levels.next_dca, RED),
+            ] {
+                let Some(price) = price else {
+                    continue;
+                };
+                chart
+                    .draw_series(DashedLineSeries::new(
+                        [(start, price), (end, price)],
+                        8,
+                        6,
+                        color.stroke_width(2),
+                    ))
+                    .map_err(|e| render_error(&e))?
+                    .label(format!("{} {:.4}", label, price))
+                    .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+            }
+        }
+
+        let orange = RGBColor(255, 140, 0);
+        for (label, points, color) in [
+            ("Buy", buys, GREEN),
+            ("DCA buy", dca_buys, orange),
+            ("Sell", sells, RED),
+        ] {
+            if points.is_empty() {
+                continue;
+            }
+            chart
+                .draw_series(
+                    points
+                        .into_iter()
+                        .map(|point| TriangleMarker::new(point, 9, color.filled())),
+                )
+                .map_err(|e| render_error(&e))?
+                .label(label)
+                .legend(move |(x, y)| TriangleMarker::new((x + 10, y), 7, color.filled()));
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .label_font(("sans-serif", 16))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(|e| render_error(&e))?;
+        root.present().map_err(|e| render_error(&e))?;
+    }
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(&pixels, CHART_SIZE.0, CHART_SIZE.1, ColorType::Rgb8)
+        .map_err(|e| render_error(&e))?;
+
+    let caption = match &levels {
+        Some(levels) => format!(
+            "{}: holding {:.6} (DCA level {}), average entry {:.4}, take profit {:.4}, next DCA {}",
+            config.pair_name(),
+            state.holding,
+            state.dca_level,
+            levels.entry,
+            levels.take_profit,
+            levels
+                .next_dca
+                .map_or("none left".to_string(), |price| format!("{:.4}", price))
+        ),
+        None => format!("{}: no open position", config.pair_name()),
+    };
+    Ok((png, caption))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hours_days_and_weeks() {
+        assert_eq!(parse_timeframe("12h"), Some(Duration::hours(12)));
+        assert_eq!(parse_timeframe("3D"), Some(Duration::days(3)));
+        assert_eq!(parse_timeframe("2w"), Some(Duration::weeks(2)));
+        assert_eq!(parse_timeframe(DEFAULT_TIMEFRAME), Some(Duration::days(3)));
+    }
+
+    #[test]
+    fn rejects_malformed_windows() {
+        for value in ["", "h", "0d", "-3d", "3m", "3", "d3", "1é", "é", "3dd"] {
+            assert_eq!(parse_timeframe(value), None, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_windows_past_a_year() {
+        assert_eq!(parse_timeframe("366d"), Some(Duration::days(366)));
+        assert_eq!(parse_timeframe("367d"), None);
+        assert_eq!(parse_timeframe("99999999999999d"), None);
+        assert_eq!(parse_timeframe("100000000w"), None);
+        assert_eq!(parse_timeframe("9223372036854775807h"), None);
+    }
+
+    #[test]
+    fn concurrent_renders_each_return_their_own_png() {
+        let config = crate::config::test_config("chart-render", "");
+        let start = Utc::now().timestamp_millis() - 3_600_000 * 24;
+        let log: String = (0..24)
+            .map(|hour| {
+                let close = 150.0 + hour as f64;
+                format!(
+                    "{},{},{},{},{},1000\n",
+                    start + hour * 3_600_000,
+                    close,
+                    close + 1.0,
+                    close - 1.0,
+                    close
+                )
+            })
+            .collect();
+        std::fs::write(config.binance_price_log_path(), log).unwrap();
+
+        let renders: Vec<_> = (0..4)
+            .map(|_| {
+                let config = config.clone();
+                std::thread::spawn(move || {
+                    draw_chart(&config, &PairState::default(), Duration::days(1))
+                })
+            })
+            .collect();
+        for render in renders {
+            let (png, caption) = render.join().unwrap().unwrap();
+            assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+            assert!(caption.ends_with("no open position"));
+        }
+        assert!(!std::path::Path::new(&config.pair_log_path("chart.png")).exists());
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::Utc;
+
+use crate::config::{Severity, StrategyConfig, VenueConfig};
+use crate::error::BotResult;
+use crate::notifier::notify;
+use crate::state_store::{PairState, StateStore};
+use crate::swap_venue::build_venue;
+use crate::token_registry::{SOL_MINT, token_registry};
+
+/// Recorded holding of a pair next to what the wallet actually holds
+#[derive(Debug, Clone)]
+pub struct Reconciliation {
+    pub recorded: f64, // `holding` in the pair state
+    pub others: f64,   // `holding` of the other pairs keeping `left_asset` in the same wallet
+    pub on_chain: f64, // `left_asset` balance reported by the venue, less the fee reserve
+    pub reserve: f64,  // Native SOL left out of `on_chain` for fees and rent
+    pub tolerance: f64,
+}
+
+/// SOL set aside for fees when `left_asset` is native SOL in a live Jupiter wallet
+pub fn fee_reserve(config: &StrategyConfig) -> f64 {
+    let wallet_sol = config.left_asset == SOL_MINT
+        && !config.is_paper()
+        && matches!(config.venue, VenueConfig::Jupiter);
+    if wallet_sol {
+        config.sol_fee_reserve
+    } else {
+        0.0
+    }
+}
+
+impl Reconciliation {
+    pub fn difference(&self) -> f64 {
+        self.on_chain - self.recorded - self.others
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.difference().abs() <= self.tolerance
+    }
+
+    pub async fn report(&self, config: &StrategyConfig) -> String {
+        let symbol = token_registry().symbol(&config.left_asset).await;
+        let pair_name = config.pair_name();
+        let reserve = if self.reserve > 0.0 {
+            format!(", {:.6} kept for fees", self.reserve)
+        } else {
+            String::new()
+        };
+        let others = if self.others > 0.0 {
+            format!(" (+{:.6} held by other pairs)", self.others)
+        } else {
+            String::new()
+        };
+        if self.is_consistent() {
+            format!(
+                "✅ *{}*: recorded {:.6} {}{} matches the wallet ({:.6}{})",
+                pair_name, self.recorded, symbol, others, self.on_chain, reserve
+            )
+        } else {
+            format!(
+                "⚠️ *{}*: recorded {:.6} {}{} but the wallet holds {:.6}{} ({:+.6})\n\
+                 Use /adopt\\_onchain {} to adopt the wallet value, or /stop\\_trading {} to pause the pair.",
+                pair_name,
+                self.recorded,
+                symbol,
+                others,
+                self.on_chain,
+                reserve,
+                self.difference(),
+                pair_name,
+                pair_name
+            )
+        }
+    }
+}
+
+/// Whether two live pairs keep `left_asset` in the same wallet (paper and mock balances are per pair)
+fn shares_wallet(a: &StrategyConfig, b: &StrategyConfig) -> bool {
+    if a.is_paper() || b.is_paper() || a.left_asset != b.left_asset {
+        return false;
+    }
+    match (&a.venue, &b.venue) {
+        (VenueConfig::Jupiter, VenueConfig::Jupiter) => true,
+        (VenueConfig::Osmosis { address: a, .. }, VenueConfig::Osmosis { address: b, .. }) => {
+            a == b
+        }
+        _ => false,
+    }
+}
+
+/// Compares the recorded holdings with the wallet balance of `left_asset`. Every pair of
+/// `pairs` sharing the wallet and asset counts, since the wallet holds their tokens together.
+pub async fn check_holdings(
+    config: &StrategyConfig,
+    pairs: &[StrategyConfig],
+) -> BotResult<Reconciliation> {
+    let state = StateStore::open(config)?.load()?;
+    let mut others = 0.0;
+    for other in pairs
+        .iter()
+        .filter(|other| other.pair_name() != config.pair_name() && shares_wallet(config, other))
+    {
+        others += StateStore::open(other)?.load()?.holding;
+    }
+    let venue = build_venue(config)?;
+    let reserve = fee_reserve(config);
+    let on_chain = (venue.balance(&config.left_asset).await? - reserve).max(0.0);
+    Ok(Reconciliation {
+        recorded: state.holding,
+        others,
+        on_chain,
+        reserve,
+        tolerance: config.reconcile_tolerance,
+    })
+}
+
+/// Replaces the recorded holding with the current wallet balance (less the fee reserve and
+/// what the other pairs on the wallet hold)
+pub async fn adopt_on_chain(
+    config: &StrategyConfig,
+    pairs: &[StrategyConfig],
+) -> BotResult<(Reconciliation, PairState)> {
+    let reconciliation = check_holdings(config, pairs).await?;
+    let store = StateStore::open(config)?;
+    let mut state = store.load()?;
+    let holding = (reconciliation.on_chain - reconciliation.others).max(0.0);
+    state.record_adjustment(holding, Utc::now());
+    store.save(&state)?;
+    Ok((reconciliation, state))
+}
+
+/// Checks every pair once at startup and alerts about the ones that do not match
+pub async fn reconcile_at_startup(configs: Vec<StrategyConfig>) {
+    for config in &configs {
+        match check_holdings(config, &configs).await {
+            Ok(reconciliation) => {
+                let report = reconciliation.report(config).await;
+                println!("{}", report);
+                if !reconciliation.is_consistent() {
+                    notify(Severity::Warning, &report).await;
+                }
+            }
+            Err(e) => {
+                let message = format!("❌ Could not reconcile {}: {}", config.pair_name(), e);
+                eprintln!("{}", message);
+                notify(Severity::Warning, &message).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    #[test]
+    fn fee_reserve_only_applies_to_live_wallet_sol() {
+        let live = test_config("reserve-live", "sol_fee_reserve = 0.02");
+        assert_eq!(fee_reserve(&live), 0.02);
+
+        let paper = test_config("reserve-paper", "mode = \"paper\"");
+        assert_eq!(fee_reserve(&paper), 0.0);
+
+        let mut jup = test_config("reserve-jup", "");
+        jup.left_asset = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN".to_string();
+        assert_eq!(fee_reserve(&jup), 0.0);
+    }
+
+    #[test]
+    fn mismatch_is_measured_against_the_tolerance() {
+        let reconciliation = Reconciliation {
+            recorded: 1.0,
+            others: 0.0,
+            on_chain: 1.0005,
+            reserve: 0.05,
+            tolerance: 0.001,
+        };
+        assert!(reconciliation.is_consistent());
+        let off = Reconciliation {
+            on_chain: 1.01,
+            ..reconciliation
+        };
+        assert!(!off.is_consistent());
+        assert!((off.difference() - 0.01).abs() < 1e-12);
+
+        // Tokens recorded by another pair on the same wallet are not a surplus
+        let shared = Reconciliation {
+            others: 0.01,
+            ..off
+        };
+        assert!(shared.is_consistent());
+    }
+
+    #[test]
+    fn live_pairs_on_one_wallet_share_the_left_asset() {
+        let usdc = test_config("shares-usdc", "");
+        let mut usdt = test_config("shares-usdt", "");
+        usdt.right_asset = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string();
+        assert!(shares_wallet(&usdc, &usdt));
+
+        let paper = test_config("shares-paper", "mode = \"paper\"");
+        assert!(!shares_wallet(&usdc, &paper));
+
+        let mut jup = usdt.clone();
+        jup.left_asset = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN".to_string();
+        assert!(!shares_wallet(&usdc, &jup));
+    }
+}
@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use crate::config::StrategyConfig;
+use crate::state_store::PairState;
+use crate::strategy::{DataNeeds, Intent, MarketSnapshot};
+
+/// Data the limits look at, on top of what the strategy asked for
+pub fn needs(config: &StrategyConfig, position: &PairState, needs: DataNeeds) -> DataNeeds {
+    // With `allocated_capital` the pair reports its equity to the portfolio drawdown limit
+    let limited = config.allocated_capital.is_some()
+        || config.stop_loss_percentage.is_some()
+        || config.stop_loss_amount.is_some();
+    DataNeeds {
+        exit_quote: needs.exit_quote || (position.holding > 0.0 && limited),
+        ..needs
+    }
+}
+
+/// Equity of the pair alone: `allocated_capital`, minus what its buys spent, plus what its
+/// sells returned and what the holding sells for now. The wallet balance is left out, so
+/// pairs sharing a wallet never move each other's equity. Tokens adopted with an `adjust`
+/// were never paid for by the pair, so their share of the holding and of later sells is
+/// left out of the value.
+pub fn equity(
+    config: &StrategyConfig,
+    market: &MarketSnapshot,
+    position: &PairState,
+) -> Option<f64> {
+    let capital = config.allocated_capital?;
+    let (mut net_spent, mut held, mut adopted) = (0.0, 0.0, 0.0);
+    for trade in &position.trades {
+        let adopted_share = if held > 0.0 { adopted / held } else { 0.0 };
+        match trade.trade_type.as_str() {
+            "buy" => {
+                net_spent += trade.amount_token_a;
+                held += trade.amount_token_b;
+            }
+            "sell" => {
+                net_spent -= trade.amount_token_b * (1.0 - adopted_share);
+                (held, adopted) = (0.0, 0.0);
+            }
+            "partial_sell" => {
+                net_spent -= trade.amount_token_b * (1.0 - adopted_share);
+                adopted -= trade.amount_token_a * adopted_share;
+                held = (held - trade.amount_token_a).max(0.0);
+            }
+            // `amount_token_a` → `amount_token_b`: a gain is adopted, a loss uses up adopted tokens first
+            "adjust" => {
+                adopted = (adopted + trade.amount_token_b - trade.amount_token_a).max(0.0);
+                held = trade.amount_token_b;
+            }
+            _ => {}
+        }
+    }
+    let holding_value = if position.holding > 0.0 {
+        market.exit_quote? * (1.0 - (adopted / position.holding).min(1.0))
+    } else {
+        0.0
+    };
+    Some(capital - net_spent + holding_value)
+}
+
+/// Replaces the strategy intent when a limit trips: a stop-loss or the portfolio drawdown
+/// limit (`drawdown`, the reason it tripped) sells the position and pauses, a DCA buy past
+/// `max_dca_level` pauses
+pub fn guard(
+    config: &StrategyConfig,
+    market: &MarketSnapshot,
+    position: &PairState,
+    drawdown: Option<&str>,
+    intent: Intent,
+) -> Intent {
+    if position.holding > 0.0
+        && let Some(proceeds) = market.exit_quote
+    {
+        let paid = position.open_cost();
+        let loss = paid - proceeds;
+        if let Some(pct) = config.stop_loss_percentage
+            && paid > 0.0
+            && loss >= paid * pct / 100.0
+        {
+            return Intent::Exit(format!(
+                "Stop-loss: the position is down {:.2}% (limit {}%)",
+                100.0 * loss / paid,
+                pct
+            ));
+        }
+        if let Some(amount) = config.stop_loss_amount
+            && loss >= amount
+        {
+            return Intent::Exit(format!(
+                "Stop-loss: the position lost {:.2} (limit {})",
+                loss, amount
+            ));
+        }
+    }
+
+    if let Some(reason) = drawdown {
+        let reason = reason.to_string();
+        return if position.holding > 0.0 {
+            Intent::Exit(reason)
+        } else {
+            Intent::Pause(reason)
+        };
+    }
+
+    match intent {
+        Intent::Buy(order)
+            if order.grid.is_none()
+                && config
+                    .max_dca_level
+                    .is_some_and(|max| order.dca_level > max) =>
+        {
+            if position.breaker.is_some() {
+                println!("🪜 Max DCA level reached. Holding without averaging down...");
+                Intent::Hold(Duration::from_secs(5))
+            } else {
+                Intent::Pause(format!(
+                    "Max DCA level {} reached, the next DCA buy was skipped",
+                    position.dca_level
+                ))
+            }
+        }
+        intent => intent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::strategy::BuyOrder;
+    use chrono::Utc;
+
+    fn holding(spent: f64, received: f64) -> PairState {
+        let mut position = PairState::default();
+        position.record_buy(spent, received, 0, Utc::now());
+        position
+    }
+
+    fn quoted(exit_quote: f64, quote_balance: f64) -> MarketSnapshot {
+        MarketSnapshot {
+            exit_quote: Some(exit_quote),
+            quote_balance: Some(quote_balance),
+            ..MarketSnapshot::default()
+        }
+    }
+
+    fn buy(dca_level: u32) -> Intent {
+        Intent::Buy(BuyOrder {
+            spend: 10.0,
+            dca_level,
+            grid: None,
+        })
+    }
+
+    #[test]
+    fn stop_loss_exits_at_the_percentage_or_amount() {
+        let config = test_config(
+            "stop-loss",
+            "stop_loss_percentage = 10.0\nstop_loss_amount = 30.0",
+        );
+        let position = holding(200.0, 2.0);
+        let hold = Intent::Hold(Duration::from_secs(5));
+
+        assert_eq!(
+            guard(&config, &quoted(181.0, 0.0), &position, None, hold.clone()),
+            hold
+        );
+        assert!(matches!(
+            guard(&config, &quoted(180.0, 0.0), &position, None, hold.clone()),
+            Intent::Exit(reason) if reason.contains("down 10.00%")
+        ));
+
+        let position = holding(400.0, 4.0);
+        assert!(matches!(
+            guard(&config, &quoted(370.0, 0.0), &position, None, hold),
+            Intent::Exit(reason) if reason.contains("lost 30.00")
+        ));
+    }
+
+    #[test]
+    fn equity_ignores_the_shared_wallet_balance() {
+        let config = test_config("equity", "allocated_capital = 1000.0");
+        let mut position = holding(200.0, 2.0);
+        position.record_sell(2.0, 220.0, Utc::now());
+        position.record_buy(100.0, 1.0, 0, Utc::now());
+
+        // 1000 - 200 + 220 - 100 + 95, whatever the wallet holds
+        assert_eq!(equity(&config, &quoted(95.0, 5.0), &position), Some(1015.0));
+        assert_eq!(
+            equity(&config, &quoted(95.0, 50_000.0), &position),
+            Some(1015.0)
+        );
+    }
+
+    #[test]
+    fn adopted_tokens_are_left_out_of_the_equity() {
+        let config = test_config("equity-adjust", "allocated_capital = 1000.0");
+        let mut position = holding(200.0, 2.0);
+        // 2 tokens found in the wallet and adopted: half the holding was never paid for
+        position.record_adjustment(4.0, Utc::now());
+        assert_eq!(
+            equity(&config, &quoted(400.0, 0.0), &position),
+            Some(1000.0)
+        );
+
+        // Selling half keeps the same share out of the proceeds and of what is left
+        position.record_partial_sell(2.0, 220.0, Utc::now());
+        assert_eq!(
+            equity(&config, &quoted(220.0, 0.0), &position),
+            Some(1020.0)
+        );
+        position.record_sell(2.0, 220.0, Utc::now());
+        assert_eq!(equity(&config, &quoted(0.0, 0.0), &position), Some(1020.0));
+
+        // Tokens that went missing come out of the adopted ones first, then out of the equity
+        let mut position = holding(200.0, 2.0);
+        position.record_adjustment(4.0, Utc::now());
+        position.record_adjustment(1.0, Utc::now());
+        assert_eq!(equity(&config, &quoted(100.0, 0.0), &position), Some(900.0));
+    }
+
+    #[test]
+    fn a_portfolio_drawdown_exits_or_pauses_the_pair() {
+        let config = test_config("drawdown", "allocated_capital = 1000.0");
+        let hold = Intent::Hold(Duration::from_secs(5));
+        let reason = Some("Max drawdown: portfolio equity 800.00");
+
+        assert!(matches!(
+            guard(&config, &quoted(95.0, 0.0), &holding(100.0, 1.0), reason, hold.clone()),
+            Intent::Exit(reason) if reason.starts_with("Max drawdown")
+        ));
+        assert!(matches!(
+            guard(&config, &quoted(0.0, 0.0), &PairState::default(), reason, hold),
+            Intent::Pause(reason) if reason.starts_with("Max drawdown")
+        ));
+    }
+
+    #[test]
+    fn max_dca_level_pauses_once_then_holds_after_resume() {
+        let config = test_config("max-dca-level", "max_dca_level = 2");
+        let mut position = holding(100.0, 1.0);
+        position.dca_level = 2;
+        let market = quoted(95.0, 500.0);
+
+        assert_eq!(guard(&config, &market, &position, None, buy(2)), buy(2));
+        assert!(matches!(
+            guard(&config, &market, &position, None, buy(3)),
+            Intent::Pause(reason) if reason.contains("Max DCA level 2")
+        ));
+
+        position.trip("Max DCA level 2 reached", Utc::now());
+        position.resume();
+        assert!(position.active_breaker().is_none());
+        assert_eq!(
+            guard(&config, &market, &position, None, buy(3)),
+            Intent::Hold(Duration::from_secs(5))
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_custom_error::{
+    JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY, JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+};
+use solana_client::rpc_request::{RpcError, RpcRequest, RpcResponseErrorData};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::config::{RpcEndpointConfig, StrategyConfig};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Pools shared by every pair using the same endpoint list
+static POOLS: OnceLock<Mutex<HashMap<String, Arc<RpcPool>>>> = OnceLock::new();
+
+/// Failure of a pooled JSON-RPC call
+#[derive(Debug)]
+pub enum RpcPoolError {
+    /// The node answered with a JSON-RPC error (not retried on other endpoints)
+    Rpc {
+        code: i64,
+        message: String,
+        data: Value,
+    },
+    /// Every endpoint failed or was unreachable
+    Unavailable(String),
+}
+
+impl fmt::Display for RpcPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcPoolError::Rpc { code, message, .. } => write!(f, "RPC error {}: {}", code, message),
+            RpcPoolError::Unavailable(reason) => write!(f, "No RPC endpoint available: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RpcPoolError {}
+
+#[derive(Debug, Clone, Default)]
+struct EndpointHealth {
+    healthy: bool,
+    slot: u64,
+    latency_ms: u64,
+    last_error: Option<String>,
+}
+
+struct RpcEndpoint {
+    url: String,
+    priority: u32,
+    headers: HeaderMap,
+    health: Mutex<EndpointHealth>,
+}
+
+/// Solana RPC endpoints ordered by priority, with health checks and automatic failover
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    client: Client,
+    max_slot_lag: u64,
+    max_latency_ms: u64,
+    request_id: AtomicU64,
+}
+
+/// Returns the pool of the pair, creating it (and its health check task) on first use
+pub fn rpc_pool_for(config: &StrategyConfig) -> Arc<RpcPool> {
+    let endpoints = config.rpc_endpoints();
+    let key = format!(
+        "{:?}|{}|{}|{}",
+        endpoints
+            .iter()
+            .map(|e| (
+                &e.url,
+                e.priority,
+                e.headers.iter().collect::<BTreeMap<_, _>>()
+            ))
+            .collect::<Vec<_>>(),
+        config.rpc_health_interval_secs,
+        config.rpc_max_slot_lag,
+        config.rpc_max_latency_ms
+    );
+
+    let mut pools = POOLS.get_or_init(Default::default).lock().unwrap();
+    pools
+        .entry(key)
+        .or_insert_with(|| {
+            let pool = Arc::new(RpcPool::new(
+                &endpoints,
+                config.rpc_max_slot_lag,
+                config.rpc_max_latency_ms,
+            ));
+            pool.spawn_health_checks(Duration::from_secs(config.rpc_health_interval_secs));
+            pool
+        })
+        .clone()
+}
+
+impl RpcPool {
+    pub fn new(endpoints: &[RpcEndpointConfig], max_slot_lag: u64, max_latency_ms: u64) -> Self {
+        let endpoints = endpoints
+            .iter()
+            .map(|e| {
+                let mut headers = HeaderMap::new();
+                for (name, value) in &e.headers {
+                    match (
+                        HeaderName::from_bytes(name.as_bytes()),
+                        HeaderValue::from_str(value),
+                    ) {
+                        (Ok(name), Ok(value)) => {
+                            headers.insert(name, value);
+                        }
+                        _ => eprintln!("⚠️ Skipping invalid RPC header {} for {}", name, e.url),
+                    }
+                }
+                RpcEndpoint {
+                    url: e.url.clone(),
+                    priority: e.priority,
+                    headers,
+                    // Assume healthy until the first check says otherwise
+                    health: Mutex::new(EndpointHealth {
+                        healthy: true,
+                        ..Default::default()
+                    }),
+                }
+            })
+            .collect();
+
+        Self {
+            endpoints,
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            max_slot_lag,
+            max_latency_ms,
+            request_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Healthy endpoints first, then by priority and latency
+    fn ordered(&self) -> Vec<&RpcEndpoint> {
+        let mut endpoints: Vec<(&RpcEndpoint, EndpointHealth)> = self
+            .endpoints
+            .iter()
+            .map(|e| (e, e.health.lock().unwrap().clone()))
+            .collect();
+        endpoints.sort_by_key(|(e, h)| (!h.healthy, e.priority, h.latency_ms));
+        endpoints.into_iter().map(|(e, _)| e).collect()
+    }
+
+    fn mark(&self, endpoint: &RpcEndpoint, error: Option<String>) {
+        let mut health = endpoint.health.lock().unwrap();
+        if let Some(e) = &error
+            && health.healthy
+        {
+            println!("⚠️ RPC {} marked unhealthy: {}", endpoint.url, e);
+        }
+        health.healthy = error.is_none();
+        health.last_error = error;
+    }
+
+    async fn post(&self, endpoint: &RpcEndpoint, body: &Value) -> Result<Value, String> {
+        let res = self
+            .client
+            .post(&endpoint.url)
+            .headers(endpoint.headers.clone())
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !res.status().is_success() {
+            return Err(format!("HTTP {}", res.status()));
+        }
+        res.json().await.map_err(|e| e.to_string())
+    }
+
+    /// Sends a JSON-RPC request, moving to the next endpoint on transport errors
+    pub async fn send_json(&self, body: Value) -> Result<Value, RpcPoolError> {
+        let mut last_error = "no endpoints configured".to_string();
+        for endpoint in self.ordered() {
+            let json = match self.post(endpoint, &body).await {
+                Ok(json) => json,
+                Err(e) => {
+                    self.mark(endpoint, Some(e.clone()));
+                    last_error = format!("{}: {}", endpoint.url, e);
+                    continue;
+                }
+            };
+
+            if let Some(error) = json.get("error").filter(|e| e.is_object()) {
+                let code = error["code"].as_i64().unwrap_or_default();
+                let message = error["message"].as_str().unwrap_or_default().to_string();
+                if code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY {
+                    self.mark(endpoint, Some(message.clone()));
+                    last_error = format!("{}: {}", endpoint.url, message);
+                    continue;
+                }
+                return Err(RpcPoolError::Rpc {
+                    code,
+                    message,
+                    data: error["data"].clone(),
+                });
+            }
+            return Ok(json["result"].clone());
+        }
+        Err(RpcPoolError::Unavailable(last_error))
+    }
+
+    /// Calls `method` with `params` and returns its `result`
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, RpcPoolError> {
+        let id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        self.send_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))
+        .await
+    }
+
+    /// Probes every endpoint with `getSlot`, then flags slow or lagging ones
+    pub async fn check_health(&self) {
+        let body = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot" });
+        let mut probes = Vec::new();
+        for endpoint in &self.endpoints {
+            let started = Instant::now();
+            let slot = self.post(endpoint, &body).await.and_then(|json| {
+                json["result"]
+                    .as_u64()
+                    .ok_or_else(|| json["error"].to_string())
+            });
+            probes.push((slot, started.elapsed().as_millis() as u64));
+        }
+
+        let best_slot = probes
+            .iter()
+            .filter_map(|(slot, _)| slot.as_ref().ok())
+            .max()
+            .copied()
+            .unwrap_or_default();
+
+        for (endpoint, (slot, latency_ms)) in self.endpoints.iter().zip(probes) {
+            let error = match &slot {
+                Err(e) => Some(e.clone()),
+                Ok(slot) if best_slot - *slot > self.max_slot_lag => {
+                    Some(format!("{} slots behind", best_slot - slot))
+                }
+                Ok(_) if latency_ms > self.max_latency_ms => {
+                    Some(format!("{}ms latency", latency_ms))
+                }
+                Ok(_) => None,
+            };
+            if error.is_none() && !endpoint.health.lock().unwrap().healthy {
+                println!("✅ RPC {} is healthy again", endpoint.url);
+            }
+            self.mark(endpoint, error);
+
+            let mut health = endpoint.health.lock().unwrap();
+            health.latency_ms = latency_ms;
+            if let Ok(slot) = slot {
+                health.slot = slot;
+            }
+        }
+    }
+
+    fn spawn_health_checks(self: &Arc<Self>, interval: Duration) {
+        let pool = self.clone();
+        tokio::spawn(async move {
+            loop {
+                pool.check_health().await;
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
+    /// Solana `RpcClient` whose requests go through the pool
+    pub fn rpc_client(self: &Arc<Self>) -> RpcClient {
+        RpcClient::new_sender(
+            PoolSender {
+                pool: self.clone(),
+                stats: Mutex::new(RpcTransportStats::default()),
+            },
+            RpcClientConfig::with_commitment(CommitmentConfig::default()),
+        )
+    }
+
+    /// One line per endpoint, for status messages
+    pub fn status_report(&self) -> String {
+        self.ordered()
+            .iter()
+            .map(|e| {
+                let health = e.health.lock().unwrap();
+                format!(
+                    "{} {} (prio {}, slot {}, {}ms){}",
+                    if health.healthy { "🟢" } else { "🔴" },
+                    e.url,
+                    e.priority,
+                    health.slot,
+                    health.latency_ms,
+                    health
+                        .last_error
+                        .as_ref()
+                        .map(|err| format!(" — {}", err))
+                        .unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Transport plugging the pool into `RpcClient` (send, confirmation polling, balances)
+struct PoolSender {
+    pool: Arc<RpcPool>,
+    stats: Mutex<RpcTransportStats>,
+}
+
+#[async_trait]
+impl RpcSender for PoolSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: Value,
+    ) -> solana_client::client_error::Result<Value> {
+        let started = Instant::now();
+        let id = self.pool.request_id.fetch_add(1, Ordering::Relaxed);
+        let result = self
+            .pool
+            .send_json(request.build_request_json(id, params))
+            .await;
+
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.request_count += 1;
+            stats.elapsed_time += started.elapsed();
+        }
+
+        result.map_err(|e| match e {
+            RpcPoolError::Rpc {
+                code,
+                message,
+                data,
+            } => {
+                let data = match code {
+                    JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                        serde_json::from_value::<RpcSimulateTransactionResult>(data)
+                            .map(RpcResponseErrorData::SendTransactionPreflightFailure)
+                            .unwrap_or(RpcResponseErrorData::Empty)
+                    }
+                    _ => RpcResponseErrorData::Empty,
+                };
+                RpcError::RpcResponseError {
+                    code,
+                    message,
+                    data,
+                }
+                .into()
+            }
+            RpcPoolError::Unavailable(reason) => ClientError::from(ClientErrorKind::Custom(reason)),
+        })
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    fn url(&self) -> String {
+        self.pool
+            .ordered()
+            .first()
+            .map(|e| e.url.clone())
+            .unwrap_or_default()
+    }
+}
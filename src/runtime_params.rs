@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::config::StrategyConfig;
+use crate::error::{BotError, BotResult};
+use crate::state_store::write_atomic;
+
+/// Parameters that can be changed from Telegram while the pair is running
+pub const TUNABLE_PARAMS: [&str; 9] = [
+    "sell_percentage",
+    "dca_recover_percentage",
+    "r_factor",
+    "trail_percentage",
+    "cooldown_secs",
+    "min_buy_amount",
+    "min_dca_amount",
+    "slippage_bps",
+    "slippage_bps_max",
+];
+
+/// `pair_<left>_<right>_params.json`: values set with `/set`, applied over the config file
+pub struct ParamStore {
+    path: String,
+}
+
+impl ParamStore {
+    pub fn open(config: &StrategyConfig) -> Self {
+        Self {
+            path: config.pair_log_path("params.json"),
+        }
+    }
+
+    /// Reads the saved overrides (none if nothing was set yet)
+    pub fn load(&self) -> BotResult<BTreeMap<String, f64>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| BotError::StateIo(format!("Corrupted {}: {}", self.path, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The config file values with the saved overrides applied
+    pub fn effective(&self, config: &StrategyConfig) -> BotResult<StrategyConfig> {
+        let mut effective = config.clone();
+        for (name, value) in self.load()? {
+            effective
+                .set_param(&name, value)
+                .map_err(BotError::Config)?;
+        }
+        effective.validate().map_err(BotError::Config)?;
+        Ok(effective)
+    }
+
+    /// Validates and saves one override, returning the new effective config
+    pub fn set(
+        &self,
+        config: &StrategyConfig,
+        name: &str,
+        value: &str,
+    ) -> BotResult<StrategyConfig> {
+        let value: f64 = value
+            .parse()
+            .map_err(|_| BotError::Config(format!("`{}` is not a number", value)))?;
+        let mut effective = self.effective(config)?;
+        effective.set_param(name, value).map_err(BotError::Config)?;
+        effective.validate().map_err(BotError::Config)?;
+
+        let mut overrides = self.load()?;
+        overrides.insert(name.to_string(), value);
+        write_atomic(&self.path, &serde_json::to_string_pretty(&overrides)?)?;
+        Ok(effective)
+    }
+}
+
+/// Effective parameters of a pair, e.g. for `/get` and status messages
+pub fn effective_config(config: &StrategyConfig) -> BotResult<StrategyConfig> {
+    ParamStore::open(config).effective(config)
+}
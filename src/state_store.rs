@@ -0,0 +1,355 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use crate::config::StrategyConfig;
+use crate::dca_strategy::DcaLadder;
+use crate::error::{BotError, BotResult};
+use crate::grid_strategy::{GridLayout, GridLot};
+use crate::log_manager::Trade;
+
+/// Legacy per-pair files replaced by the state store
+const LEGACY_SUFFIXES: [&str; 3] = ["value.txt", "dca_level.txt", "trade_history.json"];
+
+/// Everything we know about the open position of a pair, saved as a single file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PairState {
+    pub holding: f64,   // Amount of `left_asset` held (e.g. SOL)
+    pub dca_level: u32, // DCA buys since the last sell
+    pub trades: Vec<Trade>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing_peak: Option<f64>, // Best exit price seen since the trailing take-profit armed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ladder: Option<DcaLadder>, // Planned when the position opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breaker: Option<Breaker>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid_layout: Option<GridLayout>, // Derived by the grid strategy, kept across restarts
+}
+
+/// A risk limit that stopped the pair; trading stays off until /resume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Breaker {
+    pub reason: String,
+    pub time: String,
+    // Set by /resume while a position is open and kept until it closes,
+    // so a capped DCA holds instead of pausing again
+    pub resumed: bool,
+}
+
+impl PairState {
+    /// `right_asset` paid for the open position; a partial sell releases its share of the cost
+    pub fn open_cost(&self) -> f64 {
+        // The position starts after the last sell (or after adopting a zero holding)
+        let start = self
+            .trades
+            .iter()
+            .rposition(|t| {
+                t.trade_type == "sell" || (t.trade_type == "adjust" && t.amount_token_b == 0.0)
+            })
+            .map_or(0, |idx| idx + 1);
+        let (mut cost, mut held) = (0.0, 0.0);
+        for trade in &self.trades[start..] {
+            match trade.trade_type.as_str() {
+                "buy" => {
+                    cost += trade.amount_token_a;
+                    held += trade.amount_token_b;
+                }
+                "partial_sell" if held > 0.0 => {
+                    cost *= 1.0 - (trade.amount_token_a / held).min(1.0);
+                    held -= trade.amount_token_a;
+                }
+                "adjust" => held = trade.amount_token_b,
+                _ => {}
+            }
+        }
+        cost
+    }
+
+    /// Records a buy: the bought amount is added to the holding
+    pub fn record_buy(&mut self, spent: f64, received: f64, dca_level: u32, time: DateTime<Utc>) {
+        self.push_trade("buy", spent, received, dca_level, time);
+        self.holding += received;
+        self.dca_level = dca_level;
+    }
+
+    /// Records a full exit: holding and DCA level go back to zero
+    pub fn record_sell(&mut self, sold: f64, received: f64, time: DateTime<Utc>) {
+        self.push_trade("sell", sold, received, 0, time);
+        self.holding = 0.0;
+        self.dca_level = 0;
+        self.trailing_peak = None;
+        self.ladder = None;
+        self.clear_resumed_breaker();
+    }
+
+    /// Records a sell of part of the holding, keeping the position and its DCA level
+    pub fn record_partial_sell(&mut self, sold: f64, received: f64, time: DateTime<Utc>) {
+        self.push_trade("partial_sell", sold, received, self.dca_level, time);
+        self.holding = (self.holding - sold).max(0.0);
+    }
+
+    /// Records a manual correction of the holding (`amount_token_a` → `amount_token_b`)
+    pub fn record_adjustment(&mut self, holding: f64, time: DateTime<Utc>) {
+        let dca_level = if holding == 0.0 { 0 } else { self.dca_level };
+        self.push_trade("adjust", self.holding, holding, dca_level, time);
+        self.holding = holding;
+        self.dca_level = dca_level;
+        if holding == 0.0 {
+            self.trailing_peak = None;
+            self.ladder = None;
+            self.clear_resumed_breaker();
+        }
+    }
+
+    /// A tripped limit that still keeps the pair stopped
+    pub fn active_breaker(&self) -> Option<&Breaker> {
+        self.breaker.as_ref().filter(|breaker| !breaker.resumed)
+    }
+
+    /// Records a tripped limit; the pair stays stopped until /resume
+    pub fn trip(&mut self, reason: &str, time: DateTime<Utc>) {
+        self.breaker = Some(Breaker {
+            reason: reason.to_string(),
+            time: time.to_rfc3339(),
+            resumed: false,
+        });
+    }
+
+    /// Clears a tripped limit for /resume
+    pub fn resume(&mut self) {
+        if self.holding == 0.0 {
+            self.breaker = None;
+        } else if let Some(breaker) = &mut self.breaker {
+            breaker.resumed = true;
+        }
+    }
+
+    fn clear_resumed_breaker(&mut self) {
+        self.breaker = self.breaker.take().filter(|breaker| !breaker.resumed);
+    }
+
+    fn push_trade(
+        &mut self,
+        trade_type: &str,
+        amount_token_a: f64,
+        amount_token_b: f64,
+        dca_level: u32,
+        time: DateTime<Utc>,
+    ) {
+        self.trades.push(Trade {
+            trade_type: trade_type.to_string(),
+            amount_token_a,
+            amount_token_b,
+            time: time.to_rfc3339(),
+            dca_level: Some(dca_level),
+            grid: None,
+        });
+    }
+
+    /// Marks the last recorded fill as the buy or the sell of a grid lot
+    pub fn tag_grid_lot(&mut self, lot: GridLot) {
+        if let Some(trade) = self.trades.last_mut() {
+            trade.grid = Some(lot);
+        }
+    }
+}
+
+/// `pair_<left>_<right>_state.json`, replaced atomically on every save
+pub struct StateStore {
+    path: String,
+}
+
+impl StateStore {
+    /// Opens the store of a pair, importing the legacy files the first time
+    pub fn open(config: &StrategyConfig) -> BotResult<Self> {
+        let store = Self {
+            path: config.pair_log_path("state.json"),
+        };
+        if !Path::new(&store.path).exists() {
+            store.import_legacy(config)?;
+        }
+        Ok(store)
+    }
+
+    /// Reads the saved state (empty if nothing was saved yet)
+    pub fn load(&self) -> BotResult<PairState> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| BotError::StateIo(format!("Corrupted {}: {}", self.path, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(PairState::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the whole state in one step: either the old or the new file survives a crash
+    pub fn save(&self, state: &PairState) -> BotResult<()> {
+        write_atomic(&self.path, &serde_json::to_string_pretty(state)?)
+    }
+
+    /// Builds the state from `value.txt`, `dca_level.txt` and `trade_history.json`, then
+    /// renames them to `*.imported` so they are never read again. A file that does not parse
+    /// is an error and every legacy file is left in place.
+    fn import_legacy(&self, config: &StrategyConfig) -> BotResult<()> {
+        let legacy_paths: Vec<String> = LEGACY_SUFFIXES
+            .iter()
+            .map(|suffix| config.pair_log_path(suffix))
+            .filter(|path| Path::new(path).exists())
+            .collect();
+        if legacy_paths.is_empty() {
+            return Ok(());
+        }
+
+        let read = |suffix: &str| -> BotResult<Option<(String, String)>> {
+            let path = config.pair_log_path(suffix);
+            if !Path::new(&path).exists() {
+                return Ok(None);
+            }
+            let content = fs::read_to_string(&path)?;
+            Ok(Some((path, content)))
+        };
+        let corrupt = |path: &str, problem: String| {
+            BotError::StateIo(format!(
+                "Cannot import legacy file {}: {}. Fix or remove it, nothing was imported",
+                path, problem
+            ))
+        };
+
+        let mut state = PairState::default();
+        if let Some((path, content)) = read("value.txt")? {
+            state.holding = content.trim().parse().map_err(|e| {
+                corrupt(
+                    &path,
+                    format!("{:?} is not a number: {}", content.trim(), e),
+                )
+            })?;
+        }
+        if let Some((path, content)) = read("dca_level.txt")? {
+            state.dca_level = content.trim().parse().map_err(|e| {
+                corrupt(
+                    &path,
+                    format!("{:?} is not a number: {}", content.trim(), e),
+                )
+            })?;
+        }
+        if let Some((path, content)) = read("trade_history.json")? {
+            for (idx, line) in content.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let trade = serde_json::from_str(line).map_err(|e| {
+                    corrupt(&path, format!("line {} is not a trade: {}", idx + 1, e))
+                })?;
+                state.trades.push(trade);
+            }
+        }
+        self.save(&state)?;
+
+        for path in &legacy_paths {
+            fs::rename(path, format!("{}.imported", path))?;
+        }
+        println!(
+            "📦 Imported legacy state into {}: holding {:.6}, DCA level {}, {} trades",
+            self.path,
+            state.holding,
+            state.dca_level,
+            state.trades.len()
+        );
+        Ok(())
+    }
+}
+
+/// Writes to a temp file, syncs it and renames it over `path`
+pub fn write_atomic(path: &str, contents: &str) -> BotResult<()> {
+    let tmp_path = format!("{}.tmp", path);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+
+    // Make the rename itself durable
+    if let Some(dir) = Path::new(path).parent()
+        && let Ok(dir) = File::open(if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        })
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    #[test]
+    fn partial_sells_release_their_share_of_the_cost() {
+        let now = Utc::now();
+        let mut state = PairState::default();
+        state.record_buy(100.0, 1.0, 0, now);
+        state.record_buy(50.0, 1.0, 1, now);
+        assert_eq!(state.open_cost(), 150.0);
+
+        state.record_partial_sell(0.5, 40.0, now);
+        assert_eq!(state.holding, 1.5);
+        assert_eq!(state.open_cost(), 112.5);
+
+        // A new position only counts the buys after the full exit
+        state.record_sell(1.5, 120.0, now);
+        assert_eq!(state.open_cost(), 0.0);
+        state.record_buy(30.0, 0.2, 0, now);
+        assert_eq!(state.open_cost(), 30.0);
+
+        // Adopting an empty wallet starts over as well
+        state.record_adjustment(0.0, now);
+        assert_eq!(state.open_cost(), 0.0);
+    }
+
+    #[test]
+    fn legacy_files_are_imported_once() {
+        let config = test_config("legacy-import", "");
+        fs::write(config.pair_log_path("value.txt"), "1.25\n").unwrap();
+        fs::write(config.pair_log_path("dca_level.txt"), "2").unwrap();
+        let first = r#"{"trade_type":"buy","amount_token_a":100.0,"amount_token_b":0.75,"time":"2025-01-01T00:00:00+00:00","dca_level":0}"#;
+        let second = r#"{"trade_type":"buy","amount_token_a":50.0,"amount_token_b":0.5,"time":"2025-01-02T00:00:00+00:00","dca_level":2}"#;
+        let history = config.pair_log_path("trade_history.json");
+
+        // A corrupt line stops the import and keeps every legacy file
+        fs::write(&history, format!("{}\nnot json\n{}\n", first, second)).unwrap();
+        match StateStore::open(&config) {
+            Err(BotError::StateIo(e)) => assert!(e.contains("line 2 is not a trade"), "{e}"),
+            other => panic!("expected a StateIo error, got {:?}", other.map(|_| ())),
+        }
+        assert!(!Path::new(&config.pair_log_path("state.json")).exists());
+        for suffix in LEGACY_SUFFIXES {
+            assert!(Path::new(&config.pair_log_path(suffix)).exists());
+        }
+
+        fs::write(&history, format!("{}\n\n{}\n", first, second)).unwrap();
+        let store = StateStore::open(&config).unwrap();
+        let state = store.load().unwrap();
+        assert_eq!(state.holding, 1.25);
+        assert_eq!(state.dca_level, 2);
+        assert_eq!(state.trades.len(), 2);
+        assert_eq!(state.open_cost(), 150.0);
+        for suffix in LEGACY_SUFFIXES {
+            let path = config.pair_log_path(suffix);
+            assert!(!Path::new(&path).exists());
+            assert!(Path::new(&format!("{}.imported", path)).exists());
+        }
+
+        // Later changes are never overwritten by the legacy files
+        let mut state = state;
+        state.record_sell(1.25, 160.0, Utc::now());
+        store.save(&state).unwrap();
+        fs::write(config.pair_log_path("value.txt"), "9").unwrap();
+        let reopened = StateStore::open(&config).unwrap().load().unwrap();
+        assert_eq!(reopened.holding, 0.0);
+        assert_eq!(reopened.trades.len(), 3);
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+use crate::config::{StrategyConfig, StrategyKind};
+use crate::dca_strategy::DcaStrategy;
+use crate::error::BotResult;
+use crate::grid_strategy::{GridLayout, GridLot, GridStrategy};
+use crate::state_store::PairState;
+
+/// Market data a strategy asks for, so the executor only fetches what the next decision uses
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataNeeds {
+    pub risk: bool, // Refreshed Binance history and the risk check at the current price
+    pub price_history: bool, // Refreshed Binance history, its last close and touched price buckets
+    pub quote_balance: bool, // Balance of `right_asset` (e.g. USDC)
+    pub exit_quote: bool, // `right_asset` the whole holding would sell for
+}
+
+/// `PriceTouchAnalyzer` verdict for the take-profit target at the current Binance price
+#[derive(Debug, Clone)]
+pub struct RiskAssessment {
+    pub label: String,
+    pub multiplier: f64, // Suggested share of the balance to trade
+}
+
+/// What the market looked like when the strategy decided; unrequested data is `None`
+#[derive(Debug, Clone, Default)]
+pub struct MarketSnapshot {
+    pub now: DateTime<Utc>,
+    pub price: Option<f64>,         // Last Binance close
+    pub touches: Vec<(f64, usize)>, // Price buckets of the history and how often they were hit
+    pub risk: Option<RiskAssessment>,
+    pub quote_balance: Option<f64>,
+    pub exit_quote: Option<f64>,
+}
+
+/// Spend `spend` of `right_asset`; `dca_level` and the grid lot are saved with the fill
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuyOrder {
+    pub spend: f64,
+    pub dca_level: u32,
+    pub grid: Option<GridLot>,
+}
+
+/// Sell `amount` of `left_asset`; selling the whole holding closes the position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SellOrder {
+    pub amount: f64,
+    pub grid: Option<GridLot>,
+}
+
+/// What the executor should do next
+#[derive(Debug, Clone, PartialEq)]
+pub enum Intent {
+    Buy(BuyOrder),
+    Sell(SellOrder),
+    /// Look at the market again after this long
+    Hold(Duration),
+    /// Save a new peak exit price for the trailing take-profit, then look again shortly
+    Trail(f64),
+    /// Save the grid layout derived from the price history, then look again
+    PlaceGrid(GridLayout),
+    /// Stop trading the pair until an operator uses /resume
+    Pause(String),
+    /// Sell the whole position, then pause
+    Exit(String),
+}
+
+/// Trading rules only: snapshots and the position in, intents out.
+/// Quotes, swaps, state files and notifications are left to the executor.
+pub trait Strategy: Send {
+    fn name(&self) -> &'static str;
+
+    /// Data to fetch before the next `decide`
+    fn needs(&self, config: &StrategyConfig, position: &PairState, now: DateTime<Utc>)
+    -> DataNeeds;
+
+    /// `config` includes the overrides set with /set, so it can change between calls
+    fn decide(
+        &mut self,
+        config: &StrategyConfig,
+        market: &MarketSnapshot,
+        position: &PairState,
+    ) -> BotResult<Intent>;
+}
+
+/// The strategy a pair trades with
+pub fn build_strategy(config: &StrategyConfig) -> Box<dyn Strategy> {
+    match &config.strategy {
+        StrategyKind::Dca => Box::new(DcaStrategy),
+        StrategyKind::Grid {
+            lower,
+            upper,
+            levels,
+            order_size,
+        } => Box::new(GridStrategy::new(*lower, *upper, *levels, *order_size)),
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::config::{StrategyConfig, VenueConfig};
+use crate::error::halt_reason;
+use crate::rpc_pool::{RpcPool, rpc_pool_for};
+use crate::state_store::StateStore;
+use crate::utils::run_jupiter_bot;
+
+/// One trading pair owned by the supervisor: its config, start/stop flag and task
+pub struct PairHandle {
+    pub config: StrategyConfig,
+    pub trading_flag: Arc<Mutex<bool>>,
+    pub busy: Mutex<()>, // Held while the strategy runs, so manual trades never race it
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Owns one task per trading pair and reports their status
+pub struct Supervisor {
+    pairs: Vec<Arc<PairHandle>>,
+}
+
+impl PairHandle {
+    pub fn name(&self) -> String {
+        self.config.pair_name()
+    }
+
+    /// Spawns the pair task: it waits for the trading flag and runs the strategy while it is set
+    async fn spawn(self: &Arc<Self>) {
+        let pair = self.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let busy = pair.busy.lock().await;
+                if *pair.trading_flag.lock().await && halt_reason().is_none() {
+                    run_jupiter_bot(&pair.config, pair.trading_flag.clone()).await;
+                    *pair.trading_flag.lock().await = false;
+                }
+                drop(busy);
+
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+        });
+        *self.task.lock().await = Some(task);
+    }
+
+    /// A task only finishes if the strategy panicked
+    async fn has_crashed(&self) -> bool {
+        match self.task.lock().await.as_ref() {
+            Some(task) => task.is_finished(),
+            None => true,
+        }
+    }
+
+    pub async fn status_line(&self) -> String {
+        let breaker = StateStore::open(&self.config)
+            .and_then(|store| store.load())
+            .ok()
+            .and_then(|state| state.active_breaker().map(|b| b.reason.clone()));
+        let status = if let Some(reason) = breaker {
+            format!("⛔ Stopped by a risk limit: {} (use /resume)", reason)
+        } else if self.has_crashed().await {
+            "💥 Task crashed (use /start_trading to restart)".to_string()
+        } else if *self.trading_flag.lock().await {
+            "🟢 Online".to_string()
+        } else {
+            "🔴 Offline".to_string()
+        };
+        let mode = if self.config.is_paper() {
+            " 📝 paper"
+        } else {
+            ""
+        };
+        format!("*{}*{}: {}", self.name(), mode, status)
+    }
+}
+
+impl Supervisor {
+    /// Spawns one task per configured pair, stopped until `/start_trading` unless `autostart` is set
+    pub async fn start(configs: Vec<StrategyConfig>) -> Self {
+        let mut pairs = Vec::new();
+        for config in configs {
+            let pair = Arc::new(PairHandle {
+                trading_flag: Arc::new(Mutex::new(config.autostart)),
+                config,
+                busy: Mutex::new(()),
+                task: Mutex::new(None),
+            });
+            pair.spawn().await;
+            println!("🧵 Spawned task for pair {}", pair.name());
+            pairs.push(pair);
+        }
+        Self { pairs }
+    }
+
+    /// Selects one pair by name, or all of them when no name is given
+    pub fn select(&self, name: Option<&str>) -> Result<Vec<Arc<PairHandle>>, String> {
+        match name {
+            None => Ok(self.pairs.clone()),
+            Some(name) => self
+                .pairs
+                .iter()
+                .find(|p| p.name().eq_ignore_ascii_case(name))
+                .map(|p| vec![p.clone()])
+                .ok_or_else(|| {
+                    let known: Vec<String> = self.pairs.iter().map(|p| p.name()).collect();
+                    format!("Unknown pair `{}`. Known pairs: {}", name, known.join(", "))
+                }),
+        }
+    }
+
+    /// Configs of every pair, for checks that span pairs sharing a wallet
+    pub fn configs(&self) -> Vec<StrategyConfig> {
+        self.pairs.iter().map(|p| p.config.clone()).collect()
+    }
+
+    /// Sets the trading flag, restarting the task first if it crashed
+    pub async fn set_trading(&self, pair: &Arc<PairHandle>, on: bool) -> bool {
+        if on && pair.has_crashed().await {
+            println!("♻️ Restarting crashed task for pair {}", pair.name());
+            pair.spawn().await;
+        }
+        let mut flag = pair.trading_flag.lock().await;
+        let changed = *flag != on;
+        *flag = on;
+        changed
+    }
+
+    pub async fn status_report(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(reason) = halt_reason() {
+            lines.push(format!("🚨 Bot halted: {}", reason));
+        }
+        for pair in &self.pairs {
+            lines.push(pair.status_line().await);
+        }
+
+        // RPC health, once per distinct endpoint pool
+        let mut pools: Vec<Arc<RpcPool>> = Vec::new();
+        for pair in self
+            .pairs
+            .iter()
+            .filter(|p| matches!(p.config.venue, VenueConfig::Jupiter))
+        {
+            let pool = rpc_pool_for(&pair.config);
+            if !pools.iter().any(|p| Arc::ptr_eq(p, &pool)) {
+                pools.push(pool);
+            }
+        }
+        if !pools.is_empty() {
+            lines.push("🌐 RPC endpoints:".to_string());
+        }
+        for pool in pools {
+            lines.push(pool.status_report());
+        }
+        lines.join("\n")
+    }
+}
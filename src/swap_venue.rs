@@ -0,0 +1,728 @@
+use async_trait::async_trait;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::config::{PaperConfig, StrategyConfig, VenueConfig};
+use crate::error::{BotError, BotResult};
+use crate::rpc_pool::{RpcPool, rpc_pool_for};
+use crate::state_store::write_atomic;
+use crate::token_registry::SOL_MINT;
+use crate::utils::{
+    PoolAsset, get_pool_assets, get_token_balance, get_usdc_balance, jupiter_quote, jupiter_swap,
+    osmo_address, osmo_signing_key, pool_swap, sign_tx_broadcast, simulate_swap_math,
+    sol_get_sol_balance, wait_for_tx_confirmation,
+};
+use bip32::secp256k1::ecdsa::SigningKey;
+
+pub type VenueResult<T> = BotResult<T>;
+
+/// Expected result of a swap, amounts in UI units
+#[derive(Debug, Clone)]
+pub struct SwapQuote {
+    pub out_amount: f64,
+}
+
+/// Executed swap, amounts in UI units
+#[derive(Debug, Clone)]
+pub struct SwapFill {
+    pub out_amount: f64,
+    pub tx_signature: String,
+}
+
+/// A place where the strategy can quote, swap and read balances
+#[async_trait]
+pub trait SwapVenue: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        slippage_bps: u64,
+    ) -> VenueResult<SwapQuote>;
+
+    /// Quotes, builds, signs, submits and confirms the swap. Once submitted, a swap whose
+    /// outcome cannot be confirmed is `BotError::Unconfirmed`, never a retryable error.
+    async fn swap(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        slippage_bps: u64,
+    ) -> VenueResult<SwapFill>;
+
+    async fn balance(&self, mint: &str) -> VenueResult<f64>;
+}
+
+/// Builds the venue configured for a pair (wrapped in a paper venue in paper mode)
+pub fn build_venue(config: &StrategyConfig) -> BotResult<Box<dyn SwapVenue>> {
+    if config.is_paper() {
+        let quote_source: Box<dyn SwapVenue> = match &config.venue {
+            VenueConfig::Jupiter => Box::new(JupiterVenue::quote_only(rpc_pool_for(config))),
+            VenueConfig::Osmosis {
+                pool_id,
+                address,
+                decimals,
+            } => Box::new(OsmosisVenue::quote_only(pool_id, address, decimals)),
+            _ => build_live_venue(config)?,
+        };
+        return Ok(Box::new(PaperVenue::new(
+            quote_source,
+            &config.pair_log_path("paper_balances.json"),
+            &config.right_asset,
+            &config.paper,
+        )));
+    }
+    build_live_venue(config)
+}
+
+fn build_live_venue(config: &StrategyConfig) -> BotResult<Box<dyn SwapVenue>> {
+    match &config.venue {
+        VenueConfig::Jupiter => Ok(Box::new(JupiterVenue::new(
+            rpc_pool_for(config),
+            &wallet_pk()?,
+        )?)),
+        VenueConfig::Osmosis {
+            pool_id,
+            address,
+            decimals,
+        } => Ok(Box::new(OsmosisVenue::new(
+            pool_id,
+            address,
+            decimals,
+            &osmo_wallet_mnemonic()?,
+        )?)),
+        VenueConfig::Mock {
+            prices,
+            balances,
+            fee_bps,
+        } => Ok(Box::new(MockVenue::new(
+            prices.clone(),
+            balances.clone(),
+            *fee_bps,
+        ))),
+    }
+}
+
+/// Base58 secret key of the wallet live Jupiter pairs sign with
+fn wallet_pk() -> BotResult<String> {
+    env::var("SOL_WALLET_PK")
+        .map_err(|_| BotError::Config("SOL_WALLET_PK not set in .env".to_string()))
+}
+
+/// Address of the `SOL_WALLET_PK` wallet, logged once at startup
+pub fn wallet_address() -> BotResult<Pubkey> {
+    Ok(parse_keypair(&wallet_pk()?)?.pubkey())
+}
+
+fn parse_keypair(wallet_pk: &str) -> BotResult<Keypair> {
+    let bytes = bs58::decode(wallet_pk.trim())
+        .into_vec()
+        .map_err(|e| BotError::Config(format!("SOL_WALLET_PK is not valid base58: {}", e)))?;
+    Keypair::try_from(bytes.as_slice())
+        .map_err(|e| BotError::Config(format!("SOL_WALLET_PK is not a valid keypair: {}", e)))
+}
+
+/// Mnemonic of the wallet live Osmosis pairs sign with
+fn osmo_wallet_mnemonic() -> BotResult<String> {
+    env::var("OSMO_WALLET_MNEMONIC")
+        .map_err(|_| BotError::Config("OSMO_WALLET_MNEMONIC not set in .env".to_string()))
+}
+
+// === Jupiter (Solana) ===
+
+pub struct JupiterVenue {
+    rpc: Arc<RpcPool>,
+    keypair: Option<Keypair>, // `None` for a quote-only venue
+}
+
+impl JupiterVenue {
+    pub fn new(rpc: Arc<RpcPool>, wallet_pk: &str) -> BotResult<Self> {
+        Ok(Self {
+            rpc,
+            keypair: Some(parse_keypair(wallet_pk)?),
+        })
+    }
+
+    /// A venue that can only quote, e.g. as the price source of a paper venue
+    pub fn quote_only(rpc: Arc<RpcPool>) -> Self {
+        Self { rpc, keypair: None }
+    }
+
+    fn keypair(&self) -> VenueResult<&Keypair> {
+        self.keypair
+            .as_ref()
+            .ok_or_else(|| BotError::Config("Jupiter venue is quote-only (no wallet)".to_string()))
+    }
+}
+
+#[async_trait]
+impl SwapVenue for JupiterVenue {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    async fn quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        slippage_bps: u64,
+    ) -> VenueResult<SwapQuote> {
+        let (out_amount, _) = jupiter_quote(input_mint, output_mint, amount, slippage_bps).await?;
+        Ok(SwapQuote { out_amount })
+    }
+
+    async fn swap(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        slippage_bps: u64,
+    ) -> VenueResult<SwapFill> {
+        let (out_amount, tx_signature) = jupiter_swap(
+            &self.rpc,
+            input_mint,
+            output_mint,
+            amount,
+            slippage_bps,
+            self.keypair()?,
+        )
+        .await?;
+        Ok(SwapFill {
+            out_amount,
+            tx_signature,
+        })
+    }
+
+    async fn balance(&self, mint: &str) -> VenueResult<f64> {
+        let wallet_pubkey = self.keypair()?.pubkey();
+        if mint == SOL_MINT {
+            Ok(sol_get_sol_balance(&self.rpc, &wallet_pubkey).await?)
+        } else {
+            Ok(get_usdc_balance(&self.rpc, &wallet_pubkey.to_string(), mint).await?)
+        }
+    }
+}
+
+// === Osmosis (Cosmos) ===
+
+/// Decimals of well-known Osmosis denoms, extended by `[pairs.venue.decimals]`
+const OSMOSIS_DENOMS: &[(&str, u8)] = &[
+    ("uosmo", 6),
+    ("uion", 6),
+    // ATOM
+    (
+        "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+        6,
+    ),
+    // USDC (Noble)
+    (
+        "ibc/498A0751C798A0D9A389AA3691123DADA57DAA4FE165D5C75894505B876BA6E4",
+        6,
+    ),
+];
+
+/// Swap fee of the pools the venue trades on
+const OSMOSIS_POOL_FEE: f64 = 0.003;
+/// Tx lookups (2s apart) before a broadcast swap counts as unconfirmed
+const OSMOSIS_CONFIRM_POLLS: u32 = 30;
+
+pub struct OsmosisVenue {
+    pool_id: String,
+    address: String,
+    decimals: HashMap<String, u8>,
+    signing_key: Option<SigningKey>, // `None` for a quote-only venue
+}
+
+impl OsmosisVenue {
+    pub fn new(
+        pool_id: &str,
+        address: &str,
+        decimals: &HashMap<String, u8>,
+        mnemonic: &str,
+    ) -> BotResult<Self> {
+        let signing_key = osmo_signing_key(mnemonic).map_err(|e| {
+            BotError::Config(format!(
+                "OSMO_WALLET_MNEMONIC is not a valid mnemonic: {}",
+                e
+            ))
+        })?;
+        let derived = osmo_address(&signing_key)?;
+        if derived != address {
+            return Err(BotError::Config(format!(
+                "OSMO_WALLET_MNEMONIC is the wallet {}, not venue.address {}",
+                derived, address
+            )));
+        }
+        Ok(Self {
+            signing_key: Some(signing_key),
+            ..Self::quote_only(pool_id, address, decimals)
+        })
+    }
+
+    /// A venue that can only quote and read balances, e.g. as the price source of a paper venue
+    pub fn quote_only(pool_id: &str, address: &str, decimals: &HashMap<String, u8>) -> Self {
+        Self {
+            pool_id: pool_id.to_string(),
+            address: address.to_string(),
+            decimals: decimals.clone(),
+            signing_key: None,
+        }
+    }
+
+    fn signing_key(&self) -> VenueResult<&SigningKey> {
+        self.signing_key
+            .as_ref()
+            .ok_or_else(|| BotError::Config("Osmosis venue is quote-only (no wallet)".to_string()))
+    }
+
+    /// Decimals of a denom from the config, then the built-in table; unknown denoms fail
+    fn denom_decimals(&self, denom: &str) -> VenueResult<u8> {
+        self.decimals
+            .get(denom)
+            .copied()
+            .or_else(|| {
+                OSMOSIS_DENOMS
+                    .iter()
+                    .find(|(known, _)| *known == denom)
+                    .map(|(_, decimals)| *decimals)
+            })
+            .ok_or_else(|| {
+                BotError::Config(format!(
+                    "Unknown decimals for denom {}: add it to [pairs.venue.decimals]",
+                    denom
+                ))
+            })
+    }
+
+    fn to_ui(&self, denom: &str, raw_amount: f64) -> VenueResult<f64> {
+        Ok(raw_amount / 10_f64.powi(self.denom_decimals(denom)? as i32))
+    }
+
+    /// UI amount in base units, rounded down
+    fn to_raw(&self, denom: &str, ui_amount: f64) -> VenueResult<u128> {
+        Ok((ui_amount * 10_f64.powi(self.denom_decimals(denom)? as i32)).floor() as u128)
+    }
+
+    /// Output of a swap on the pool with the given reserves (in base units)
+    fn pool_out(
+        &self,
+        (asset_a, asset_b): &(PoolAsset, PoolAsset),
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+    ) -> VenueResult<f64> {
+        let parse = |amount: &str| {
+            amount
+                .parse::<f64>()
+                .map_err(|e| BotError::VenueRejected(format!("Bad pool amount {}: {}", amount, e)))
+        };
+        // Pool reserves are in base units, amounts in and out are UI units
+        let amount_a = self.to_ui(&asset_a.token.denom, parse(&asset_a.token.amount)?)?;
+        let amount_b = self.to_ui(&asset_b.token.denom, parse(&asset_b.token.amount)?)?;
+
+        // Detect which is input/output
+        let (reserve_in, reserve_out) =
+            if asset_a.token.denom == input_mint && asset_b.token.denom == output_mint {
+                (amount_a, amount_b)
+            } else if asset_b.token.denom == input_mint && asset_a.token.denom == output_mint {
+                (amount_b, amount_a)
+            } else {
+                return Err(BotError::Config(format!(
+                    "Pool {} does not trade {} -> {}",
+                    self.pool_id, input_mint, output_mint
+                )));
+            };
+
+        // simulate_swap_math scales the amount in by 1e6 against the reserves
+        let scale = 1_000_000.0;
+        Ok(simulate_swap_math(
+            amount,
+            reserve_in * scale,
+            reserve_out * scale,
+            OSMOSIS_POOL_FEE,
+        ))
+    }
+}
+
+#[async_trait]
+impl SwapVenue for OsmosisVenue {
+    fn name(&self) -> &str {
+        "osmosis"
+    }
+
+    async fn quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        _slippage_bps: u64,
+    ) -> VenueResult<SwapQuote> {
+        let assets = get_pool_assets(&self.pool_id).await?;
+        Ok(SwapQuote {
+            out_amount: self.pool_out(&assets, input_mint, output_mint, amount)?,
+        })
+    }
+
+    async fn swap(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        slippage_bps: u64,
+    ) -> VenueResult<SwapFill> {
+        let signing_key = self.signing_key()?;
+        let quote = self
+            .quote(input_mint, output_mint, amount, slippage_bps)
+            .await?;
+        let min_out = quote.out_amount * (1.0 - slippage_bps as f64 / 10_000.0);
+
+        let (account_number, sequence, msg_any) = pool_swap(
+            &self.address,
+            &self.pool_id,
+            input_mint,
+            output_mint,
+            self.to_raw(input_mint, amount)?,
+            self.to_raw(output_mint, min_out)?,
+        )
+        .await?;
+        let txhash = sign_tx_broadcast(msg_any, signing_key, sequence, account_number).await?;
+
+        match wait_for_tx_confirmation(&txhash, OSMOSIS_CONFIRM_POLLS, 2).await {
+            Some((true, _, amounts)) => {
+                let out_amount = match amounts {
+                    Some((_, tokens_out)) => self.to_ui(output_mint, tokens_out)?,
+                    None => {
+                        println!("⚠️ No token_swapped event in {}, using the quote", txhash);
+                        quote.out_amount
+                    }
+                };
+                Ok(SwapFill {
+                    out_amount,
+                    tx_signature: txhash,
+                })
+            }
+            // The tx landed and failed: only the fee was spent
+            Some((false, log, _)) => {
+                let log = log.unwrap_or_default();
+                let message = format!("Swap {} failed: {}", txhash, log);
+                if log.contains("less than min amount") || log.contains("lesser than min amount") {
+                    Err(BotError::Slippage(message))
+                } else {
+                    Err(BotError::classify(&message))
+                }
+            }
+            None => Err(BotError::Unconfirmed(format!(
+                "Swap {} was broadcast but not found on chain",
+                txhash
+            ))),
+        }
+    }
+
+    async fn balance(&self, mint: &str) -> VenueResult<f64> {
+        let raw_balance = get_token_balance(&self.address, mint).await?;
+        self.to_ui(mint, raw_balance)
+    }
+}
+
+// === In-memory mock ===
+
+/// Fills every swap instantly at fixed prices, tracking balances in memory
+pub struct MockVenue {
+    prices: Mutex<HashMap<String, f64>>,
+    balances: Mutex<HashMap<String, f64>>,
+    fee_bps: u64,
+    tx_counter: AtomicU64,
+}
+
+impl MockVenue {
+    pub fn new(prices: HashMap<String, f64>, balances: HashMap<String, f64>, fee_bps: u64) -> Self {
+        Self {
+            prices: Mutex::new(prices),
+            balances: Mutex::new(balances),
+            fee_bps,
+            tx_counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Moves the price of a mint (used by the backtest to replay candles)
+    pub fn set_price(&self, mint: &str, price: f64) {
+        self.prices.lock().unwrap().insert(mint.to_string(), price);
+    }
+
+    fn price(&self, mint: &str) -> f64 {
+        *self.prices.lock().unwrap().get(mint).unwrap_or(&1.0)
+    }
+
+    fn expected_out(&self, input_mint: &str, output_mint: &str, amount: f64) -> f64 {
+        let gross = amount * self.price(input_mint) / self.price(output_mint);
+        gross * (1.0 - self.fee_bps as f64 / 10_000.0)
+    }
+}
+
+#[async_trait]
+impl SwapVenue for MockVenue {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        _slippage_bps: u64,
+    ) -> VenueResult<SwapQuote> {
+        Ok(SwapQuote {
+            out_amount: self.expected_out(input_mint, output_mint, amount),
+        })
+    }
+
+    async fn swap(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        _slippage_bps: u64,
+    ) -> VenueResult<SwapFill> {
+        let out_amount = self.expected_out(input_mint, output_mint, amount);
+
+        let mut balances = self.balances.lock().unwrap();
+        let available = *balances.get(input_mint).unwrap_or(&0.0);
+        if available + 1e-9 < amount {
+            return Err(BotError::InsufficientFunds(format!(
+                "{} balance: have {:.6}, need {:.6}",
+                input_mint, available, amount
+            )));
+        }
+        balances.insert(input_mint.to_string(), (available - amount).max(0.0));
+        *balances.entry(output_mint.to_string()).or_insert(0.0) += out_amount;
+
+        let n = self.tx_counter.fetch_add(1, Ordering::Relaxed) + 1;
+        Ok(SwapFill {
+            out_amount,
+            tx_signature: format!("mock-tx-{}", n),
+        })
+    }
+
+    async fn balance(&self, mint: &str) -> VenueResult<f64> {
+        Ok(*self.balances.lock().unwrap().get(mint).unwrap_or(&0.0))
+    }
+}
+
+// === Paper trading ===
+
+/// Real quotes, simulated fills: nothing is signed or sent, balances are virtual and saved on disk
+pub struct PaperVenue {
+    quote_source: Box<dyn SwapVenue>,
+    balances_path: String,
+    balances: Mutex<HashMap<String, f64>>,
+    fee_bps: u64,
+    tx_counter: AtomicU64,
+}
+
+impl PaperVenue {
+    pub fn new(
+        quote_source: Box<dyn SwapVenue>,
+        balances_path: &str,
+        funding_mint: &str,
+        paper: &PaperConfig,
+    ) -> Self {
+        let balances = fs::read_to_string(balances_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| HashMap::from([(funding_mint.to_string(), paper.starting_balance)]));
+
+        Self {
+            quote_source,
+            balances_path: balances_path.to_string(),
+            balances: Mutex::new(balances),
+            fee_bps: paper.fee_bps,
+            tx_counter: AtomicU64::new(0),
+        }
+    }
+
+    fn save(&self, balances: &HashMap<String, f64>) -> VenueResult<()> {
+        write_atomic(
+            &self.balances_path,
+            &serde_json::to_string_pretty(balances)?,
+        )
+    }
+}
+
+#[async_trait]
+impl SwapVenue for PaperVenue {
+    fn name(&self) -> &str {
+        "paper"
+    }
+
+    async fn quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        slippage_bps: u64,
+    ) -> VenueResult<SwapQuote> {
+        self.quote_source
+            .quote(input_mint, output_mint, amount, slippage_bps)
+            .await
+    }
+
+    /// Fills at the quoted `outAmount` minus the modeled fee
+    async fn swap(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: f64,
+        slippage_bps: u64,
+    ) -> VenueResult<SwapFill> {
+        let quote = self
+            .quote_source
+            .quote(input_mint, output_mint, amount, slippage_bps)
+            .await?;
+        let out_amount = quote.out_amount * (1.0 - self.fee_bps as f64 / 10_000.0);
+
+        let mut balances = self.balances.lock().unwrap();
+        let available = *balances.get(input_mint).unwrap_or(&0.0);
+        if available + 1e-9 < amount {
+            return Err(BotError::InsufficientFunds(format!(
+                "paper {} balance: have {:.6}, need {:.6}",
+                input_mint, available, amount
+            )));
+        }
+        balances.insert(input_mint.to_string(), (available - amount).max(0.0));
+        *balances.entry(output_mint.to_string()).or_insert(0.0) += out_amount;
+        self.save(&balances)?;
+
+        let n = self.tx_counter.fetch_add(1, Ordering::Relaxed) + 1;
+        Ok(SwapFill {
+            out_amount,
+            tx_signature: format!("paper-{}-{}", chrono::Utc::now().timestamp(), n),
+        })
+    }
+
+    async fn balance(&self, mint: &str) -> VenueResult<f64> {
+        Ok(*self.balances.lock().unwrap().get(mint).unwrap_or(&0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Token;
+
+    const ATOM: &str = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+
+    fn pool_asset(denom: &str, amount: &str) -> PoolAsset {
+        PoolAsset {
+            token: Token {
+                denom: denom.to_string(),
+                amount: amount.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn osmosis_amounts_use_the_denom_decimals() {
+        let decimals = HashMap::from([("ibc/WETH".to_string(), 18)]);
+        let venue = OsmosisVenue::quote_only("1", "osmo1test", &decimals);
+
+        assert_eq!(venue.to_ui("uosmo", 2_500_000.0).unwrap(), 2.5);
+        assert_eq!(venue.to_ui("ibc/WETH", 1e18).unwrap(), 1.0);
+        assert_eq!(venue.to_raw("uosmo", 2.5).unwrap(), 2_500_000);
+        assert!(matches!(
+            venue.to_ui("ibc/UNKNOWN", 1.0),
+            Err(BotError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn osmosis_quotes_match_the_mock_venue_at_the_pool_price() {
+        // 1 ATOM = 10 OSMO in both venues, with the same 0.3% fee
+        let pool = (
+            pool_asset(ATOM, "100000000000"),
+            pool_asset("uosmo", "1000000000000"),
+        );
+        let osmosis = OsmosisVenue::quote_only("1", "osmo1test", &HashMap::new());
+        let mock = MockVenue::new(
+            HashMap::from([(ATOM.to_string(), 10.0), ("uosmo".to_string(), 1.0)]),
+            HashMap::new(),
+            30,
+        );
+
+        for (input, output, amount) in [("uosmo", ATOM, 5.0), (ATOM, "uosmo", 0.5)] {
+            let expected = mock
+                .quote(input, output, amount, 50)
+                .await
+                .unwrap()
+                .out_amount;
+            let quoted = osmosis.pool_out(&pool, input, output, amount).unwrap();
+            assert!(
+                (quoted - expected).abs() / expected < 1e-5,
+                "{input} -> {output}"
+            );
+        }
+
+        // A tenth of the pool moves the price: the pool pays well below the mock price
+        let expected = mock
+            .quote("uosmo", ATOM, 100_000.0, 50)
+            .await
+            .unwrap()
+            .out_amount;
+        let quoted = osmosis.pool_out(&pool, "uosmo", ATOM, 100_000.0).unwrap();
+        assert!(quoted < expected * 0.95);
+
+        assert!(matches!(
+            osmosis.pool_out(&pool, "uion", ATOM, 1.0),
+            Err(BotError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn osmosis_wallets_must_match_the_venue_address() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        // Well-known Cosmos Hub address of this mnemonic, same key on Osmosis
+        let (_, account_id) =
+            bech32::decode("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4").unwrap();
+        let address =
+            bech32::encode::<bech32::Bech32>(bech32::Hrp::parse("osmo").unwrap(), &account_id)
+                .unwrap();
+
+        OsmosisVenue::new("1", &address, &HashMap::new(), mnemonic).unwrap();
+        for (address, mnemonic) in [
+            ("osmo1other", mnemonic),
+            (address.as_str(), "not a mnemonic"),
+        ] {
+            assert!(matches!(
+                OsmosisVenue::new("1", address, &HashMap::new(), mnemonic),
+                Err(BotError::Config(_))
+            ));
+        }
+
+        // Without a wallet nothing is signed or sent
+        let quote_only = OsmosisVenue::quote_only("1", &address, &HashMap::new());
+        assert!(matches!(
+            quote_only.swap("uosmo", ATOM, 1.0, 50).await,
+            Err(BotError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn wallet_keys_are_decoded_without_panicking() {
+        let keypair = Keypair::new();
+        assert_eq!(
+            parse_keypair(&keypair.to_base58_string()).unwrap().pubkey(),
+            keypair.pubkey()
+        );
+
+        for bad in ["not-base58-0OIl", "3yZe7d", ""] {
+            assert!(matches!(parse_keypair(bad), Err(BotError::Config(_))));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use crate::commands;
+use crate::config::{Role, TelegramConfig, TelegramUser};
+
+/// One line of the audit log
+#[derive(Serialize)]
+struct AuditEntry<'a> {
+    time: String,
+    user_id: Option<i64>,
+    user: Option<&'a str>,
+    role: Option<Role>,
+    command: &'a str,
+    allowed: bool,
+}
+
+/// Checks every Telegram command against `[[telegram.users]]` and writes it to the audit log
+pub struct Authorizer {
+    users: Vec<TelegramUser>,
+    audit_path: String, // JSON line per command, e.g. `logs/solana/telegram_audit.log`
+}
+
+impl Authorizer {
+    pub fn new(config: &TelegramConfig, audit_path: &str) -> Self {
+        if config.users.is_empty() {
+            println!(
+                "⚠️ No [[telegram.users]] configured: everyone in TELEGRAM_CHAT_ID has the admin role"
+            );
+        }
+        Self {
+            users: config.users.clone(),
+            audit_path: audit_path.to_string(),
+        }
+    }
+
+    /// Role of a sender, if they may use the bot at all
+    pub fn role_of(&self, user_id: Option<i64>) -> Option<Role> {
+        if self.users.is_empty() {
+            return Some(Role::Admin);
+        }
+        let user_id = user_id?;
+        self.users
+            .iter()
+            .find(|user| user.id == user_id)
+            .map(|user| user.role)
+    }
+
+    /// Ok if the sender may run the command, otherwise the reply to send back
+    pub fn authorize(
+        &self,
+        user_id: Option<i64>,
+        username: Option<&str>,
+        text: &str,
+    ) -> Result<(), String> {
+        let command = text.split_whitespace().next().unwrap_or_default();
+        // Unknown commands are answered by the parser
+        let Some(required) =
+            commands::find(command.split('@').next().unwrap_or_default()).map(|spec| spec.role)
+        else {
+            return Ok(());
+        };
+        let role = self.role_of(user_id);
+        let allowed = role.is_some_and(|role| role >= required);
+
+        // Configured names are easier to read than Telegram usernames
+        let name = user_id
+            .and_then(|id| self.users.iter().find(|user| user.id == id))
+            .and_then(|user| user.name.as_deref())
+            .or(username);
+        self.audit(&AuditEntry {
+            time: chrono::Utc::now().to_rfc3339(),
+            user_id,
+            user: name,
+            role,
+            command: text,
+            allowed,
+        });
+
+        match role {
+            _ if allowed => Ok(()),
+            Some(role) => {
+                println!("⛔ Denied {} to {:?} ({:?})", command, name, role);
+                Err(format!(
+                    "⛔ `{}` needs the {:?} role, you are {:?}",
+                    command, required, role
+                ))
+            }
+            None => {
+                println!("⛔ Denied {} to unknown user {:?}", command, user_id);
+                Err("⛔ You are not authorized to use this bot".to_string())
+            }
+        }
+    }
+
+    /// A failed write is logged, it never blocks a command
+    fn audit(&self, entry: &AuditEntry) {
+        let result = serde_json::to_string(entry)
+            .map_err(std::io::Error::from)
+            .and_then(|line| {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.audit_path)?;
+                writeln!(file, "{}", line)
+            });
+        if let Err(e) = result {
+            eprintln!("⚠️ Failed to write audit log {}: {}", self.audit_path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authorizer(test: &str, users: &str) -> (Authorizer, String) {
+        let config: TelegramConfig = toml::from_str(users).unwrap();
+        let audit_path = std::env::temp_dir()
+            .join(format!("tradeRS-bot-{}-{}.log", test, std::process::id()))
+            .display()
+            .to_string();
+        let _ = std::fs::remove_file(&audit_path);
+        (Authorizer::new(&config, &audit_path), audit_path)
+    }
+
+    #[test]
+    fn roles_include_the_commands_of_lower_roles() {
+        let (auth, audit_path) = authorizer(
+            "auth-roles",
+            r#"
+[[users]]
+id = 1
+role = "viewer"
+
+[[users]]
+id = 2
+name = "ops"
+role = "operator"
+
+[[users]]
+id = 3
+role = "admin"
+"#,
+        );
+
+        assert!(auth.authorize(Some(1), None, "/pnl week").is_ok());
+        assert!(
+            auth.authorize(Some(1), None, "/stop_trading")
+                .unwrap_err()
+                .contains("needs the Operator role")
+        );
+        assert!(
+            auth.authorize(Some(2), None, "/stop_trading@my_bot")
+                .is_ok()
+        );
+        assert!(auth.authorize(Some(2), None, "/buy 50").is_err());
+        assert!(auth.authorize(Some(3), None, "/buy 50").is_ok());
+        assert_eq!(
+            auth.authorize(Some(4), Some("stranger"), "/status"),
+            Err("⛔ You are not authorized to use this bot".to_string())
+        );
+        assert!(auth.authorize(None, None, "/status").is_err());
+        // Left to the parser, which answers with the usage
+        assert!(auth.authorize(Some(4), None, "/moon").is_ok());
+
+        let audit = std::fs::read_to_string(audit_path).unwrap();
+        assert_eq!(audit.lines().count(), 7);
+        assert!(audit.contains(r#""user":"ops""#));
+    }
+
+    #[test]
+    fn no_configured_users_means_admin() {
+        let (auth, _) = authorizer("auth-open", "");
+        assert_eq!(auth.role_of(None), Some(Role::Admin));
+        assert!(auth.authorize(Some(42), None, "/panic_sell").is_ok());
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::error::{BotError, BotResult};
+
+/// Telegram rejects longer messages
+const MAX_MESSAGE_LEN: usize = 4096;
+/// Telegram allows about one message per second in a chat
+const MIN_CHAT_INTERVAL: Duration = Duration::from_millis(1100);
+const MAX_ATTEMPTS: u32 = 5;
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Characters that must be escaped in MarkdownV2 text
+const MARKDOWN_V2_SPECIAL: &str = "_*[]()~`>#+-=|{}.!\\";
+
+/// Queue drained by the background sender, created on first use
+static OUTBOX: OnceLock<UnboundedSender<OutboundRequest>> = OnceLock::new();
+
+/// One Bot API call waiting to be sent
+struct OutboundRequest {
+    method: String,
+    chat_id: Option<String>, // Rate limited per chat, `None` for calls not tied to a chat
+    params: Vec<(String, String)>,
+    plain_text: Option<String>, // Sent without formatting if Telegram cannot parse the Markdown
+    photo: Option<Vec<u8>>,     // PNG uploaded as the `photo` field of a multipart form
+}
+
+/// Queues a message for the configured chat, escaped for MarkdownV2 and split if too long.
+/// `reply_markup` (e.g. an inline keyboard) is attached to the last part.
+pub fn send_text(text: &str, reply_markup: Option<String>) -> BotResult<()> {
+    let chat_id = chat_id()?;
+    let parts = split_message(text, MAX_MESSAGE_LEN);
+    let last = parts.len() - 1;
+    for (idx, part) in parts.into_iter().enumerate() {
+        let mut params = vec![
+            ("chat_id".to_string(), chat_id.clone()),
+            ("text".to_string(), to_markdown_v2(&part)),
+            ("parse_mode".to_string(), "MarkdownV2".to_string()),
+        ];
+        if idx == last
+            && let Some(markup) = &reply_markup
+        {
+            params.push(("reply_markup".to_string(), markup.clone()));
+        }
+        push(OutboundRequest {
+            method: "sendMessage".to_string(),
+            chat_id: Some(chat_id.clone()),
+            params,
+            plain_text: Some(part),
+            photo: None,
+        })?;
+    }
+    Ok(())
+}
+
+/// Queues any other Bot API call, in the configured chat unless `in_chat` is false
+pub fn call(method: &str, params: &[(&str, String)], in_chat: bool) -> BotResult<()> {
+    let chat_id = if in_chat { Some(chat_id()?) } else { None };
+    let mut params: Vec<(String, String)> = params
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect();
+    if let Some(chat_id) = &chat_id {
+        params.push(("chat_id".to_string(), chat_id.clone()));
+    }
+    push(OutboundRequest {
+        method: method.to_string(),
+        chat_id,
+        params,
+        plain_text: None,
+        photo: None,
+    })
+}
+
+/// Queues a PNG for the configured chat with a plain-text caption
+pub fn send_photo(png: Vec<u8>, caption: &str) -> BotResult<()> {
+    let chat_id = chat_id()?;
+    push(OutboundRequest {
+        method: "sendPhoto".to_string(),
+        chat_id: Some(chat_id.clone()),
+        params: vec![
+            ("chat_id".to_string(), chat_id),
+            ("caption".to_string(), caption.to_string()),
+        ],
+        plain_text: None,
+        photo: Some(png),
+    })
+}
+
+fn chat_id() -> BotResult<String> {
+    env::var("TELEGRAM_CHAT_ID")
+        .map_err(|_| BotError::Config("TELEGRAM_CHAT_ID not set in .env".to_string()))
+}
+
+fn push(request: OutboundRequest) -> BotResult<()> {
+    let token = env::var("TELEGRAM_HTTP_API")
+        .map_err(|_| BotError::Config("TELEGRAM_HTTP_API not set in .env".to_string()))?;
+    let outbox = OUTBOX.get_or_init(|| {
+        let (sender, receiver) = unbounded_channel();
+        tokio::spawn(run_outbox(receiver, token));
+        sender
+    });
+    outbox
+        .send(request)
+        .map_err(|_| BotError::Network("Telegram outbox is closed".to_string()))
+}
+
+/// Sends the queued calls in order, one chat message at a time
+async fn run_outbox(mut receiver: UnboundedReceiver<OutboundRequest>, token: String) {
+    let client = reqwest::Client::new();
+    let mut last_sent: HashMap<String, Instant> = HashMap::new();
+    while let Some(request) = receiver.recv().await {
+        if let Some(chat_id) = &request.chat_id
+            && let Some(last) = last_sent.get(chat_id)
+        {
+            tokio::time::sleep(MIN_CHAT_INTERVAL.saturating_sub(last.elapsed())).await;
+        }
+        deliver(&client, &token, &request).await;
+        if let Some(chat_id) = request.chat_id {
+            last_sent.insert(chat_id, Instant::now());
+        }
+    }
+}
+
+/// Retries rate limits (after `retry_after`), server and network errors with backoff
+async fn deliver(client: &reqwest::Client, token: &str, request: &OutboundRequest) {
+    let url = format!("https://api.telegram.org/bot{}/{}", token, request.method);
+    let mut params = request.params.clone();
+    let mut backoff = Duration::from_secs(1);
+    for attempt in 1..=MAX_ATTEMPTS {
+        let builder = client.post(&url);
+        let builder = match &request.photo {
+            Some(png) => {
+                let mut form = reqwest::multipart::Form::new();
+                for (key, value) in &params {
+                    form = form.text(key.clone(), value.clone());
+                }
+                let photo = reqwest::multipart::Part::bytes(png.clone())
+                    .file_name("chart.png")
+                    .mime_str("image/png")
+                    .expect("image/png is a valid MIME type");
+                builder.multipart(form.part("photo", photo))
+            }
+            None => builder.form(&params),
+        };
+        let wait = match builder.send().await {
+            Ok(res) if res.status().is_success() => {
+                if request.method == "sendMessage" || request.method == "sendPhoto" {
+                    println!("✅ Message sent to Telegram!");
+                }
+                return;
+            }
+            Ok(res) if res.status().as_u16() == 429 => {
+                let body: serde_json::Value = res.json().await.unwrap_or_default();
+                let retry_after = body["parameters"]["retry_after"]
+                    .as_u64()
+                    .map(Duration::from_secs)
+                    .unwrap_or(backoff);
+                println!(
+                    "⏳ Telegram rate limit on {}, retrying in {}s",
+                    request.method,
+                    retry_after.as_secs()
+                );
+                retry_after
+            }
+            Ok(res) if res.status().is_server_error() => {
+                eprintln!("⚠️ Telegram {} failed: {}", request.method, res.status());
+                backoff
+            }
+            Ok(res) => {
+                let body = res.text().await.unwrap_or_default();
+                match &request.plain_text {
+                    // Formatting bug: send the message unformatted rather than lose it
+                    Some(text) if body.contains("can't parse entities") => {
+                        eprintln!(
+                            "⚠️ Telegram could not parse the message, sending it as plain text"
+                        );
+                        params.retain(|(key, _)| key != "parse_mode" && key != "text");
+                        params.push(("text".to_string(), text.clone()));
+                        continue;
+                    }
+                    _ => {
+                        eprintln!("❌ Telegram {} failed: {}", request.method, body);
+                        return;
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("⚠️ Telegram {} failed: {}", request.method, e);
+                backoff
+            }
+        };
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(wait).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+    eprintln!(
+        "❌ Telegram {} dropped after {} attempts",
+        request.method, MAX_ATTEMPTS
+    );
+}
+
+/// Converts the bot's messages (`*bold*`, `` `code` ``, `\_` escapes) to MarkdownV2,
+/// escaping every other special character
+pub fn to_markdown_v2(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut bold = false;
+    let mut idx = 0;
+    while idx < chars.len() {
+        let c = chars[idx];
+        match c {
+            '\\' if chars
+                .get(idx + 1)
+                .is_some_and(|next| MARKDOWN_V2_SPECIAL.contains(*next)) =>
+            {
+                out.push('\\');
+                out.push(chars[idx + 1]);
+                idx += 1;
+            }
+            '`' if let Some(len) = chars[idx + 1..].iter().position(|c| *c == '`') => {
+                // Inside code only ` and \ need escaping, and the closing ` cannot occur
+                out.push('`');
+                for c in &chars[idx + 1..idx + 1 + len] {
+                    if *c == '\\' {
+                        out.push('\\');
+                    }
+                    out.push(*c);
+                }
+                out.push('`');
+                idx += len + 1;
+            }
+            '*' if bold || chars[idx + 1..].contains(&'*') => {
+                out.push('*');
+                bold = !bold;
+            }
+            c if MARKDOWN_V2_SPECIAL.contains(c) => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+        idx += 1;
+    }
+    out
+}
+
+/// Splits on line breaks so every part stays under `limit` once escaped
+pub fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let fits = |part: &str| to_markdown_v2(part).encode_utf16().count() <= limit;
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        let candidate = if current.is_empty() {
+            line.to_string()
+        } else {
+            format!("{}\n{}", current, line)
+        };
+        if fits(&candidate) {
+            current = candidate;
+            continue;
+        }
+        if !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        if fits(line) {
+            current = line.to_string();
+        } else {
+            // A single huge line: escaping at most doubles it and emojis take 2 UTF-16 units
+            let chars: Vec<char> = line.chars().collect();
+            for chunk in chars.chunks(limit / 4) {
+                parts.push(chunk.iter().collect());
+            }
+        }
+    }
+    if !current.is_empty() || parts.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_v2_keeps_our_markup_and_escapes_the_rest() {
+        assert_eq!(
+            to_markdown_v2("🎉 *Buy successful!* [SOL/USDC]\nSpent `1.5` *USDC* (2.5%)"),
+            "🎉 *Buy successful\\!* \\[SOL/USDC\\]\nSpent `1.5` *USDC* \\(2\\.5%\\)"
+        );
+        assert_eq!(
+            to_markdown_v2("Use /start\\_trading"),
+            "Use /start\\_trading"
+        );
+        // Unpaired markers are plain characters
+        assert_eq!(to_markdown_v2("2 * 3 = 6"), "2 \\* 3 \\= 6");
+        assert_eq!(to_markdown_v2("a ` b"), "a \\` b");
+        // Inside code only the backslash is escaped
+        assert_eq!(to_markdown_v2("`C:\\logs_1.txt`"), "`C:\\\\logs_1.txt`");
+    }
+
+    #[test]
+    fn split_message_keeps_every_escaped_part_under_the_limit() {
+        assert_eq!(split_message("", 100), vec![String::new()]);
+        assert_eq!(split_message("a.b\nc.d", 100), vec!["a.b\nc.d"]);
+
+        let text = format!(
+            "{}\n{}\n{}",
+            "1.0 ".repeat(10),
+            "🟢.".repeat(40),
+            "x".repeat(30)
+        );
+        let parts = split_message(&text, 50);
+        assert!(parts.len() > 2);
+        for part in &parts {
+            assert!(
+                to_markdown_v2(part).encode_utf16().count() <= 50,
+                "{part:?}"
+            );
+        }
+        assert_eq!(parts.concat().replace('\n', ""), text.replace('\n', ""));
+    }
+}
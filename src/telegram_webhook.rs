@@ -0,0 +1,132 @@
+use axum::Router;
+use axum::extract::{Json, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum_server::tls_rustls::RustlsConfig;
+use serde_json::Value;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::config::WebhookConfig;
+use crate::error::{BotError, BotResult, ErrorAction};
+
+/// Header Telegram sets to the `secret_token` given to `setWebhook`
+const SECRET_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+struct WebhookState {
+    secret: String,
+    updates: UnboundedSender<Value>,
+}
+
+/// Registers the webhook with Telegram and forwards every verified update to `updates`.
+/// `drop_pending` discards the updates Telegram queued while the bot was not running.
+pub async fn serve_webhook(
+    config: WebhookConfig,
+    token: String,
+    drop_pending: bool,
+    updates: UnboundedSender<Value>,
+) -> BotResult<()> {
+    let secret = env::var("TELEGRAM_WEBHOOK_SECRET")
+        .map_err(|_| BotError::Config("TELEGRAM_WEBHOOK_SECRET not set in .env".to_string()))?;
+    if secret.is_empty()
+        || secret.len() > 256
+        || !secret
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(BotError::Config(
+            "TELEGRAM_WEBHOOK_SECRET must be 1-256 characters of A-Z, a-z, 0-9, _ and -"
+                .to_string(),
+        ));
+    }
+    // Both checked with the config
+    let addr: SocketAddr = config
+        .listen
+        .parse()
+        .map_err(|e| BotError::Config(format!("Bad webhook listen address: {}", e)))?;
+    let path = reqwest::Url::parse(&config.url)
+        .map_err(|e| BotError::Config(format!("Bad webhook URL: {}", e)))?
+        .path()
+        .to_string();
+
+    let app = Router::new()
+        .route(&path, post(receive_update))
+        .with_state(Arc::new(WebhookState {
+            secret: secret.clone(),
+            updates,
+        }));
+    // Listening before Telegram is told where to send updates
+    let server = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => {
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let tls = RustlsConfig::from_pem_file(cert, key).await?;
+            tokio::spawn(axum_server::bind_rustls(addr, tls).serve(app.into_make_service()))
+        }
+        _ => tokio::spawn(axum_server::bind(addr).serve(app.into_make_service())),
+    };
+
+    // Retried while Telegram is unreachable, given up if it refuses the URL
+    while let Err(e) = set_webhook(&token, &config.url, &secret, drop_pending).await {
+        if e.action() != ErrorAction::Retry {
+            server.abort();
+            return Err(e);
+        }
+        eprintln!(
+            "⚠️ Failed to register the Telegram webhook, retrying: {}",
+            e
+        );
+        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+    }
+    println!(
+        "🌐 Telegram webhook listening on {} for {}",
+        addr, config.url
+    );
+    server
+        .await
+        .map_err(|e| BotError::Network(format!("Webhook server crashed: {}", e)))?
+        .map_err(|e| BotError::Network(format!("Webhook server on {} failed: {}", addr, e)))
+}
+
+async fn receive_update(
+    State(state): State<Arc<WebhookState>>,
+    headers: HeaderMap,
+    Json(update): Json<Value>,
+) -> StatusCode {
+    let secret = headers.get(SECRET_HEADER).and_then(|v| v.to_str().ok());
+    if secret != Some(state.secret.as_str()) {
+        println!("⛔ Rejected a webhook call without the secret token");
+        return StatusCode::UNAUTHORIZED;
+    }
+    match state.updates.send(update) {
+        Ok(()) => StatusCode::OK,
+        // Telegram retries later
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+async fn set_webhook(token: &str, url: &str, secret: &str, drop_pending: bool) -> BotResult<()> {
+    let body: Value = reqwest::Client::new()
+        .post(format!("https://api.telegram.org/bot{}/setWebhook", token))
+        .form(&[
+            ("url", url),
+            ("secret_token", secret),
+            ("allowed_updates", r#"["message","callback_query"]"#),
+            (
+                "drop_pending_updates",
+                if drop_pending { "true" } else { "false" },
+            ),
+        ])
+        .send()
+        .await?
+        .json()
+        .await?;
+    if body["ok"].as_bool() != Some(true) {
+        return Err(BotError::Config(format!(
+            "Telegram refused the webhook: {}",
+            body["description"]
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::config::RpcEndpointConfig;
+use crate::rpc_pool::RpcPool;
+use crate::state_store::write_atomic;
+
+pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Used when no pair config initialized the registry
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+const DEFAULT_CACHE_PATH: &str = "logs/solana/token_registry.json";
+
+/// Metaplex Token Metadata program, owner of the metadata account of most SPL mints
+const METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+/// Well-known mints, used when the RPC cannot be reached
+const STATIC_TOKENS: &[(&str, &str, u8)] = &[
+    (SOL_MINT, "SOL", 9),
+    ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "USDC", 6),
+    ("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", "USDT", 6),
+    ("JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN", "JUP", 6),
+    ("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", "BONK", 5),
+    ("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So", "mSOL", 9),
+    ("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn", "JitoSOL", 9),
+];
+
+static REGISTRY: OnceLock<TokenRegistry> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub mint: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Resolves decimals and symbol of SPL mints: disk cache → mint account and its
+/// metadata → static list
+pub struct TokenRegistry {
+    rpc: Arc<RpcPool>,
+    cache_path: String,
+    tokens: Mutex<HashMap<String, TokenInfo>>,
+}
+
+/// Sets the RPC and cache location of the process-wide registry (call once at startup)
+pub fn init_token_registry(rpc: Arc<RpcPool>, log_dir: &str) {
+    let registry = TokenRegistry::new(rpc, &format!("{}/token_registry.json", log_dir));
+    if REGISTRY.set(registry).is_err() {
+        eprintln!("⚠️ Token registry already initialized");
+    }
+}
+
+pub fn token_registry() -> &'static TokenRegistry {
+    REGISTRY.get_or_init(|| {
+        let endpoint = RpcEndpointConfig {
+            url: DEFAULT_RPC_URL.to_string(),
+            priority: 0,
+            headers: HashMap::new(),
+        };
+        let rpc = Arc::new(RpcPool::new(&[endpoint], u64::MAX, u64::MAX));
+        TokenRegistry::new(rpc, DEFAULT_CACHE_PATH)
+    })
+}
+
+fn static_token(mint: &str) -> Option<TokenInfo> {
+    STATIC_TOKENS
+        .iter()
+        .find(|(m, _, _)| *m == mint)
+        .map(|(m, symbol, decimals)| TokenInfo {
+            mint: m.to_string(),
+            symbol: symbol.to_string(),
+            decimals: *decimals,
+        })
+}
+
+impl TokenRegistry {
+    pub fn new(rpc: Arc<RpcPool>, cache_path: &str) -> Self {
+        let tokens: HashMap<String, TokenInfo> = fs::read_to_string(cache_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            rpc,
+            cache_path: cache_path.to_string(),
+            tokens: Mutex::new(tokens),
+        }
+    }
+
+    pub async fn resolve(&self, mint: &str) -> Result<TokenInfo, String> {
+        if let Some(info) = self.tokens.lock().unwrap().get(mint) {
+            return Ok(info.clone());
+        }
+
+        let info = match self.fetch_mint(mint).await {
+            Ok((decimals, extension_symbol)) => {
+                let symbol = match static_token(mint).map(|t| t.symbol).or(extension_symbol) {
+                    Some(symbol) => Some(symbol),
+                    None => self.fetch_metadata_symbol(mint).await,
+                };
+                TokenInfo {
+                    mint: mint.to_string(),
+                    symbol: symbol.unwrap_or_else(|| mint.chars().take(4).collect()),
+                    decimals,
+                }
+            }
+            Err(e) => {
+                let fallback = static_token(mint).ok_or_else(|| {
+                    format!(
+                        "Unknown decimals for mint {} (RPC lookup failed: {})",
+                        mint, e
+                    )
+                })?;
+                println!(
+                    "⚠️ Mint lookup failed for {} ({}), using static decimals {}",
+                    fallback.symbol, e, fallback.decimals
+                );
+                return Ok(fallback); // Not cached, so the next call retries the RPC
+            }
+        };
+
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.insert(mint.to_string(), info.clone());
+        if let Ok(json) = serde_json::to_string_pretty(&*tokens)
+            && let Err(e) = write_atomic(&self.cache_path, &json)
+        {
+            eprintln!("⚠️ Failed to write token cache {}: {}", self.cache_path, e);
+        }
+        Ok(info)
+    }
+
+    /// Reads `decimals` from the SPL mint account, with the symbol of its Token-2022
+    /// metadata extension if it has one
+    async fn fetch_mint(&self, mint: &str) -> Result<(u8, Option<String>), String> {
+        let result = self
+            .rpc
+            .request(
+                "getAccountInfo",
+                serde_json::json!([mint, { "encoding": "jsonParsed" }]),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let info = &result["value"]["data"]["parsed"]["info"];
+        let decimals = info["decimals"]
+            .as_u64()
+            .ok_or("Account is not an SPL mint")?;
+        let symbol = info["extensions"].as_array().and_then(|extensions| {
+            extensions
+                .iter()
+                .find(|e| e["extension"] == "tokenMetadata")
+                .and_then(|e| e["state"]["symbol"].as_str())
+                .map(str::trim)
+                .filter(|symbol| !symbol.is_empty())
+                .map(str::to_string)
+        });
+        Ok((u8::try_from(decimals).map_err(|e| e.to_string())?, symbol))
+    }
+
+    /// Reads the symbol from the Metaplex metadata account of the mint, if there is one
+    async fn fetch_metadata_symbol(&self, mint: &str) -> Option<String> {
+        let program_id = Pubkey::from_str(METADATA_PROGRAM_ID).ok()?;
+        let mint_key = Pubkey::from_str(mint).ok()?;
+        let (metadata, _) = Pubkey::find_program_address(
+            &[b"metadata", program_id.as_ref(), mint_key.as_ref()],
+            &program_id,
+        );
+        let result = self
+            .rpc
+            .request(
+                "getAccountInfo",
+                serde_json::json!([metadata.to_string(), { "encoding": "base64" }]),
+            )
+            .await
+            .inspect_err(|e| println!("⚠️ Metadata lookup failed for {}: {}", mint, e))
+            .ok()?;
+        let data = general_purpose::STANDARD
+            .decode(result["value"]["data"][0].as_str()?)
+            .ok()?;
+        metadata_symbol(&data)
+    }
+
+    pub async fn symbol(&self, mint: &str) -> String {
+        match self.resolve(mint).await {
+            Ok(info) => info.symbol,
+            Err(_) => mint.chars().take(4).collect(),
+        }
+    }
+
+    /// UI amount (e.g. 1.5 SOL) → base units (e.g. 1_500_000_000 lamports)
+    pub async fn to_base_units(&self, mint: &str, amount: f64) -> Result<u64, String> {
+        let info = self.resolve(mint).await?;
+        Ok((amount * 10_f64.powi(info.decimals as i32)) as u64)
+    }
+
+    /// Base units → UI amount
+    pub async fn to_ui_amount(&self, mint: &str, raw_amount: f64) -> Result<f64, String> {
+        let info = self.resolve(mint).await?;
+        Ok(raw_amount / 10_f64.powi(info.decimals as i32))
+    }
+}
+
+/// Symbol field of a Metaplex metadata account: key, update authority and mint, then the
+/// Borsh strings `name` and `symbol` (padded with NULs)
+fn metadata_symbol(data: &[u8]) -> Option<String> {
+    fn read_string(data: &[u8], offset: usize) -> Option<(String, usize)> {
+        let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let bytes = data.get(offset + 4..offset + 4 + len)?;
+        let text = String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string();
+        Some((text, offset + 4 + len))
+    }
+
+    let (_name, offset) = read_string(data, 1 + 32 + 32)?;
+    let (symbol, _) = read_string(data, offset)?;
+    (!symbol.is_empty()).then_some(symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn borsh_string(text: &str, padded_len: usize) -> Vec<u8> {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.resize(padded_len, 0);
+        let mut out = (bytes.len() as u32).to_le_bytes().to_vec();
+        out.extend(bytes);
+        out
+    }
+
+    #[test]
+    fn metadata_symbol_skips_the_name_and_padding() {
+        let mut data = vec![4; 1 + 32 + 32];
+        data.extend(borsh_string("Wrapped Thing", 32));
+        data.extend(borsh_string("WTHG", 10));
+        data.extend(borsh_string("https://example.com/meta.json", 200));
+        assert_eq!(metadata_symbol(&data).as_deref(), Some("WTHG"));
+
+        let mut blank = vec![4; 1 + 32 + 32];
+        blank.extend(borsh_string("", 32));
+        blank.extend(borsh_string("", 10));
+        assert_eq!(metadata_symbol(&blank), None);
+        assert_eq!(metadata_symbol(&data[..70]), None);
+    }
+}
@@ -0,0 +1,339 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::StrategyConfig;
+use crate::error::{BotError, BotResult};
+use crate::grid_strategy::replay_grid;
+use crate::log_manager::Trade;
+use crate::state_store::StateStore;
+use crate::swap_venue::build_venue;
+use crate::token_registry::token_registry;
+
+/// Trades listed by `/history` when no count is given
+pub const DEFAULT_HISTORY_LEN: usize = 10;
+/// Keeps a `/history` answer within one Telegram message
+pub const MAX_HISTORY_LEN: usize = 50;
+
+/// A completed buy → sell round trip
+#[derive(Debug, Clone)]
+pub struct Cycle {
+    pub opened: DateTime<Utc>, // First buy of the cycle
+    pub closed: DateTime<Utc>, // The sell
+    pub paid: f64,             // `right_asset` spent on all the buys
+    pub received: f64,         // `right_asset` received from the partial sells and the sell
+}
+
+impl Cycle {
+    pub fn profit(&self) -> f64 {
+        self.received - self.paid
+    }
+
+    pub fn hold_time(&self) -> Duration {
+        self.closed - self.opened
+    }
+}
+
+/// Time window of `/pnl`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PnlPeriod {
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl PnlPeriod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "day" => Some(PnlPeriod::Day),
+            "week" => Some(PnlPeriod::Week),
+            "month" => Some(PnlPeriod::Month),
+            "all" => Some(PnlPeriod::All),
+            _ => None,
+        }
+    }
+
+    /// Start of the window ending at `now`
+    pub fn since(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            PnlPeriod::Day => Some(now - Duration::days(1)),
+            PnlPeriod::Week => Some(now - Duration::weeks(1)),
+            PnlPeriod::Month => Some(now - Duration::days(30)),
+            PnlPeriod::All => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            PnlPeriod::Day => "last 24h",
+            PnlPeriod::Week => "last 7 days",
+            PnlPeriod::Month => "last 30 days",
+            PnlPeriod::All => "all time",
+        }
+    }
+}
+
+pub fn trade_time(trade: &Trade) -> BotResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&trade.time)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| BotError::StateIo(format!("Bad trade time {:?}: {}", trade.time, e)))
+}
+
+/// Groups the trade log into completed cycles (the open position is left out)
+pub fn completed_cycles(trades: &[Trade]) -> BotResult<Vec<Cycle>> {
+    let mut cycles = Vec::new();
+    let mut opened = None;
+    let mut paid = 0.0;
+    let mut received = 0.0;
+    for trade in trades {
+        match trade.trade_type.as_str() {
+            "buy" => {
+                if opened.is_none() {
+                    opened = Some(trade_time(trade)?);
+                }
+                paid += trade.amount_token_a;
+            }
+            "sell" => {
+                let closed = trade_time(trade)?;
+                cycles.push(Cycle {
+                    opened: opened.unwrap_or(closed),
+                    closed,
+                    paid,
+                    received: received + trade.amount_token_b,
+                });
+                opened = None;
+                paid = 0.0;
+                received = 0.0;
+            }
+            "partial_sell" => received += trade.amount_token_b,
+            // Adopting an empty wallet drops the position without a sell
+            "adjust" if trade.amount_token_b == 0.0 => {
+                opened = None;
+                paid = 0.0;
+                received = 0.0;
+            }
+            _ => {}
+        }
+    }
+    Ok(cycles)
+}
+
+/// e.g. `2d 4h`, `3h 12m` or `45m`
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub fn paper_tag(config: &StrategyConfig) -> &'static str {
+    if config.is_paper() {
+        " 📝 [PAPER]"
+    } else {
+        ""
+    }
+}
+
+/// Realized PnL of the cycles closed in `period`, plus the unrealized PnL of the open position
+pub async fn pnl_report(config: &StrategyConfig, period: PnlPeriod) -> BotResult<String> {
+    let state = StateStore::open(config)?.load()?;
+    let since = period.since(Utc::now());
+    let cycles: Vec<Cycle> = completed_cycles(&state.trades)?
+        .into_iter()
+        .filter(|cycle| since.is_none_or(|since| cycle.closed >= since))
+        .collect();
+    let right_symbol = token_registry().symbol(&config.right_asset).await;
+
+    let mut lines = vec![format!(
+        "💹 *{}*{} PnL ({})",
+        config.pair_name(),
+        paper_tag(config),
+        period.label()
+    )];
+    if cycles.is_empty() {
+        lines.push("No completed cycles".to_string());
+    } else {
+        let realized: f64 = cycles.iter().map(Cycle::profit).sum();
+        let paid: f64 = cycles.iter().map(|cycle| cycle.paid).sum();
+        let wins = cycles.iter().filter(|cycle| cycle.profit() > 0.0).count();
+        let hold_time = cycles
+            .iter()
+            .map(Cycle::hold_time)
+            .fold(Duration::zero(), |total, hold| total + hold)
+            / cycles.len() as i32;
+        lines.push(format!(
+            "💰 Realized: {:+.2} {} ({:+.2}%)",
+            realized,
+            right_symbol,
+            if paid > 0.0 {
+                100.0 * realized / paid
+            } else {
+                0.0
+            }
+        ));
+        lines.push(format!("🔁 Cycles: {} ({} winning)", cycles.len(), wins));
+        lines.push(format!(
+            "⏱️ Average hold time: {}",
+            format_duration(hold_time)
+        ));
+    }
+
+    // The open position is valued at the current sell quote
+    if state.holding > 0.0 {
+        let paid = state.open_cost();
+        let quote = match build_venue(config) {
+            Ok(venue) => {
+                venue
+                    .quote(&config.left_asset, &config.right_asset, state.holding, 50)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        lines.push(match quote {
+            Ok(quote) => format!(
+                "📦 Unrealized: {:+.2} {} on {:.2} invested (DCA level {})",
+                quote.out_amount - paid,
+                right_symbol,
+                paid,
+                state.dca_level
+            ),
+            Err(e) => format!("📦 Unrealized: unavailable ({})", e),
+        });
+    } else {
+        lines.push("📦 No open position".to_string());
+    }
+    Ok(lines.join("\n"))
+}
+
+/// The last `count` trades, newest first
+pub async fn history_report(config: &StrategyConfig, count: usize) -> BotResult<String> {
+    let state = StateStore::open(config)?.load()?;
+    let left_symbol = token_registry().symbol(&config.left_asset).await;
+    let right_symbol = token_registry().symbol(&config.right_asset).await;
+
+    let mut lines = vec![format!(
+        "🧾 *{}*{} last {} trades",
+        config.pair_name(),
+        paper_tag(config),
+        count.min(state.trades.len())
+    )];
+    if state.trades.is_empty() {
+        lines.push("No trades yet".to_string());
+    }
+    for trade in state.trades.iter().rev().take(count) {
+        let time = trade_time(trade)
+            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| trade.time.clone());
+        let dca_level = trade.dca_level.unwrap_or(0);
+        let line = match trade.trade_type.as_str() {
+            "buy" => format!(
+                "🟢 BUY {:.2} {} → {:.6} {} (DCA {})",
+                trade.amount_token_a, right_symbol, trade.amount_token_b, left_symbol, dca_level
+            ),
+            "sell" => format!(
+                "🔴 SELL {:.6} {} → {:.2} {}",
+                trade.amount_token_a, left_symbol, trade.amount_token_b, right_symbol
+            ),
+            "partial_sell" => format!(
+                "🟠 PARTIAL SELL {:.6} {} → {:.2} {}",
+                trade.amount_token_a, left_symbol, trade.amount_token_b, right_symbol
+            ),
+            other => format!(
+                "🔄 {} {:.6} → {:.6} {}",
+                other.to_uppercase(),
+                trade.amount_token_a,
+                trade.amount_token_b,
+                left_symbol
+            ),
+        };
+        lines.push(format!("`{}` {}", time, line));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Fills and realized profit of each grid level (highest first), plus the lots still open
+pub async fn grid_report(config: &StrategyConfig) -> BotResult<String> {
+    let state = StateStore::open(config)?.load()?;
+    let right_symbol = token_registry().symbol(&config.right_asset).await;
+    let (mut levels, open) = replay_grid(&state.trades);
+    levels.sort_by(|a, b| b.lot.buy.total_cmp(&a.lot.buy));
+
+    let mut lines = vec![format!(
+        "🕸️ *{}*{} grid",
+        config.pair_name(),
+        paper_tag(config)
+    )];
+    if levels.is_empty() {
+        lines.push("No grid fills yet".to_string());
+        return Ok(lines.join("\n"));
+    }
+    for level in &levels {
+        let open_here = open.iter().filter(|lot| lot.lot == level.lot).count();
+        lines.push(format!(
+            "`{:.4} → {:.4}` {} buys, {} sells, {:+.2} {}{}",
+            level.lot.buy,
+            level.lot.sell,
+            level.buys,
+            level.sells,
+            level.profit,
+            right_symbol,
+            if open_here > 0 { " 📦 open" } else { "" }
+        ));
+    }
+    let realized: f64 = levels.iter().map(|level| level.profit).sum();
+    let round_trips: usize = levels.iter().map(|level| level.sells).sum();
+    lines.push(format!(
+        "💰 Realized: {:+.2} {} over {} round trips",
+        realized, right_symbol, round_trips
+    ));
+    lines.push(format!(
+        "📦 Open lots: {} ({:.2} {} invested)",
+        open.len(),
+        open.iter().map(|lot| lot.cost).sum::<f64>(),
+        right_symbol
+    ));
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_store::PairState;
+
+    #[test]
+    fn cycles_run_from_the_first_buy_to_the_sell() {
+        let start = Utc::now() - Duration::days(3);
+        let mut state = PairState::default();
+        state.record_buy(100.0, 1.0, 0, start);
+        state.record_buy(50.0, 1.0, 1, start + Duration::hours(2));
+        state.record_partial_sell(0.5, 45.0, start + Duration::hours(3));
+        state.record_sell(1.5, 130.0, start + Duration::hours(5));
+        // Dropped by adopting an empty wallet, then an open position: neither completes
+        state.record_buy(20.0, 0.2, 0, start + Duration::days(1));
+        state.record_adjustment(0.0, start + Duration::days(1) + Duration::hours(1));
+        state.record_buy(30.0, 0.3, 0, start + Duration::days(2));
+
+        let cycles = completed_cycles(&state.trades).unwrap();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].paid, 150.0);
+        assert_eq!(cycles[0].received, 175.0);
+        assert_eq!(cycles[0].profit(), 25.0);
+        assert_eq!(cycles[0].hold_time(), Duration::hours(5));
+        assert_eq!(format_duration(cycles[0].hold_time()), "5h 0m");
+    }
+
+    #[test]
+    fn bad_trade_times_are_reported() {
+        let mut state = PairState::default();
+        state.record_buy(100.0, 1.0, 0, Utc::now());
+        state.trades[0].time = "yesterday".to_string();
+        assert!(matches!(
+            completed_cycles(&state.trades),
+            Err(BotError::StateIo(_))
+        ));
+    }
+}
@@ -4,32 +4,38 @@ use reqwest::{Client, Error};
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::str::FromStr;
 use tokio::time::{Duration, sleep};
 
 // Cosmos Deps
-// use cosmrs::{
-//     Coin, Denom,
-//     crypto::secp256k1::SigningKey,
-//     tx::{self, Fee, SignDoc, SignerInfo},
-// };
-// use osmosis_std::types::osmosis::{
-//     gamm::v1beta1::MsgSwapExactAmountIn, poolmanager::v1beta1::SwapAmountInRoute,
-// };
-// use prost::Message;
+use bech32::{Bech32, Hrp};
+use bip32::secp256k1::ecdsa::{Signature as CosmosSignature, SigningKey, signature::Signer as _};
+use bip32::{PublicKey as _, XPrv};
+use bip39::Mnemonic;
+use prost::Message;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
 use std::error::Error as StdError;
 
 // Solana Deps
+use solana_client::client_error::{ClientError, ClientErrorKind};
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_request::TokenAccountsFilter;
+use solana_client::rpc_request::RpcError;
 use solana_sdk::{
-    native_token::lamports_to_sol,
+    commitment_config::CommitmentConfig,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     transaction::VersionedTransaction,
 };
 
-use spl_associated_token_account::get_associated_token_address;
+use std::sync::Arc;
+
+use crate::error::BotError;
+use crate::rpc_pool::RpcPool;
+use crate::token_registry::{SOL_MINT, token_registry};
+
+/// Signature status checks (2s apart) before a sent swap counts as unconfirmed,
+/// longer than a blockhash stays valid
+const SWAP_CONFIRM_POLLS: u32 = 45;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PoolAsset {
@@ -74,10 +80,154 @@ pub struct AccountWrapper {
     pub account: BaseAccount,
 }
 
+const OSMOSIS_CHAIN_ID: &str = "osmosis-1";
+/// First account of a Cosmos mnemonic, the one Keplr and osmosisd use
+const OSMOSIS_HD_PATH: &str = "m/44'/118'/0'/0/0";
+const OSMOSIS_GAS_LIMIT: u64 = 300_000;
+const OSMOSIS_FEE_UOSMO: u64 = 7_500; // 0.025 uosmo per gas
+const SIGN_MODE_DIRECT: i32 = 1;
+
+// Protobuf messages of a signed Osmosis swap (the cosmrs / osmosis-std types, field for field)
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ProtoAny {
+    #[prost(string, tag = "1")]
+    pub type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoCoin {
+    #[prost(string, tag = "1")]
+    denom: String,
+    #[prost(string, tag = "2")]
+    amount: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct SwapAmountInRoute {
+    #[prost(uint64, tag = "1")]
+    pool_id: u64,
+    #[prost(string, tag = "2")]
+    token_out_denom: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MsgSwapExactAmountIn {
+    #[prost(string, tag = "1")]
+    sender: String,
+    #[prost(message, repeated, tag = "2")]
+    routes: Vec<SwapAmountInRoute>,
+    #[prost(message, optional, tag = "3")]
+    token_in: Option<ProtoCoin>,
+    #[prost(string, tag = "4")]
+    token_out_min_amount: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TxBody {
+    #[prost(message, repeated, tag = "1")]
+    messages: Vec<ProtoAny>,
+    #[prost(string, tag = "2")]
+    memo: String,
+    #[prost(uint64, tag = "3")]
+    timeout_height: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoPubKey {
+    #[prost(bytes = "vec", tag = "1")]
+    key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ModeInfoSingle {
+    #[prost(int32, tag = "1")]
+    mode: i32,
+}
+
+// `ModeInfo.sum` is a oneof: `single` is its field 1
+#[derive(Clone, PartialEq, prost::Message)]
+struct ModeInfo {
+    #[prost(message, optional, tag = "1")]
+    single: Option<ModeInfoSingle>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct SignerInfo {
+    #[prost(message, optional, tag = "1")]
+    public_key: Option<ProtoAny>,
+    #[prost(message, optional, tag = "2")]
+    mode_info: Option<ModeInfo>,
+    #[prost(uint64, tag = "3")]
+    sequence: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Fee {
+    #[prost(message, repeated, tag = "1")]
+    amount: Vec<ProtoCoin>,
+    #[prost(uint64, tag = "2")]
+    gas_limit: u64,
+    #[prost(string, tag = "3")]
+    payer: String,
+    #[prost(string, tag = "4")]
+    granter: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct AuthInfo {
+    #[prost(message, repeated, tag = "1")]
+    signer_infos: Vec<SignerInfo>,
+    #[prost(message, optional, tag = "2")]
+    fee: Option<Fee>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct SignDoc {
+    #[prost(bytes = "vec", tag = "1")]
+    body_bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    auth_info_bytes: Vec<u8>,
+    #[prost(string, tag = "3")]
+    chain_id: String,
+    #[prost(uint64, tag = "4")]
+    account_number: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TxRaw {
+    #[prost(bytes = "vec", tag = "1")]
+    body_bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    auth_info_bytes: Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    signatures: Vec<Vec<u8>>,
+}
+
 // COSMOS UTILS START
+/// Signing key of the first account of a Cosmos mnemonic
+pub fn osmo_signing_key(
+    mnemonic: &str,
+) -> Result<SigningKey, Box<dyn std::error::Error + Send + Sync>> {
+    let mnemonic = Mnemonic::parse(mnemonic.trim())?;
+    let seed = mnemonic.to_seed("");
+    let xprv = XPrv::derive_from_path(seed, &OSMOSIS_HD_PATH.parse()?)?;
+    Ok(xprv.private_key().clone())
+}
+
+/// `osmo1...` address of a key: bech32 of RIPEMD-160(SHA-256(compressed public key))
+pub fn osmo_address(
+    signing_key: &SigningKey,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let public_key = signing_key.verifying_key().to_bytes();
+    let account_id = Ripemd160::digest(Sha256::digest(public_key));
+    Ok(bech32::encode::<Bech32>(Hrp::parse("osmo")?, &account_id)?)
+}
+
 pub async fn get_pool_assets(
     pool_id: &str,
-) -> Result<(PoolAsset, PoolAsset), Box<dyn std::error::Error>> {
+) -> Result<(PoolAsset, PoolAsset), Box<dyn std::error::Error + Send + Sync>> {
     let url = format!(
         "https://osmosis-api.polkachu.com/osmosis/gamm/v1beta1/pools/{}",
         pool_id
@@ -95,22 +245,6 @@ pub async fn get_pool_assets(
     }
 }
 
-pub async fn get_token_price_by_pool(pool_id: &str) -> Result<f64, Box<dyn std::error::Error>> {
-    let (token_a, token_b) = get_pool_assets(pool_id).await?;
-
-    let amount_a: f64 = token_a.token.amount.parse()?;
-    let amount_b: f64 = token_b.token.amount.parse()?;
-
-    let price = amount_b / amount_a;
-
-    println!(
-        "1 {} ≈ {} {}",
-        token_a.token.denom, price, token_b.token.denom
-    );
-
-    Ok(price)
-}
-
 pub async fn get_wallet_balance(address: &str) -> Result<HashMap<String, f64>, Error> {
     let url = format!(
         "https://osmosis-api.polkachu.com/cosmos/bank/v1beta1/balances/{}",
@@ -128,7 +262,7 @@ pub async fn get_wallet_balance(address: &str) -> Result<HashMap<String, f64>, E
             balance
                 .denom
                 .split('/')
-                .last()
+                .next_back()
                 .unwrap_or(&balance.denom)
                 .to_string()
         } else {
@@ -144,7 +278,7 @@ pub async fn get_wallet_balance(address: &str) -> Result<HashMap<String, f64>, E
 pub async fn get_token_balance(
     address: &str,
     denom: &str,
-) -> Result<f64, Box<dyn std::error::Error>> {
+) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
     let balances = get_wallet_balance(address).await?;
 
     // Try exact match
@@ -153,11 +287,10 @@ pub async fn get_token_balance(
     }
 
     // Try without "ibc/" prefix if present
-    if denom.starts_with("ibc/") {
-        let raw_hash = &denom[4..]; // removes "ibc/"
-        if let Some(balance) = balances.get(raw_hash) {
-            return Ok(*balance);
-        }
+    if let Some(raw_hash) = denom.strip_prefix("ibc/")
+        && let Some(balance) = balances.get(raw_hash)
+    {
+        return Ok(*balance);
     }
 
     // Try suffix match
@@ -168,147 +301,192 @@ pub async fn get_token_balance(
         }
     }
 
-    println!("Token '{}' not found. Available tokens:", denom);
+    // The bank module only lists denoms the wallet holds
+    println!(
+        "Token '{}' not found, balance is 0. Available tokens:",
+        denom
+    );
     for k in balances.keys() {
         println!("  - {}", k);
     }
 
-    Err(format!("Token {} not found in wallet", denom).into())
-}
-
-// pub async fn pool_swap(
-//     address: &str,
-//     pool_id: &str,
-//     input_token_denom: &str,
-//     output_token_denom: &str,
-//     amount_in: f64,
-//     slippage_tolerance: f64,
-// ) -> Result<(u64, u64, cosmrs::Any), Box<dyn std::error::Error>> {
-//     // === Fetch account_number and sequence ===
-//     let client = Client::new();
-//     let url = format!(
-//         "https://osmosis-api.polkachu.com/cosmos/auth/v1beta1/accounts/{}",
-//         address
-//     );
-//     let res = client.get(&url).send().await?;
-//     let account_data: AccountWrapper = res.json().await?;
-
-//     let account_number = account_data.account.account_number.parse::<u64>()?;
-//     let sequence = account_data.account.sequence.parse::<u64>()?;
-
-//     // === Get price and simulate output ===
-//     let (asset_a, asset_b) = get_pool_assets(pool_id).await?;
-//     let amount_a: f64 = asset_a.token.amount.parse()?;
-//     let amount_b: f64 = asset_b.token.amount.parse()?;
-
-//     // Detect which is input/output
-//     let (reserve_in, reserve_out) = if asset_a.token.denom == input_token_denom {
-//         (amount_a, amount_b)
-//     } else {
-//         (amount_b, amount_a)
-//     };
+    Ok(0.0)
+}
 
-//     let estimated_out = simulate_swap_math(amount_in, reserve_in, reserve_out, 0.003);
-//     let min_out = estimated_out * (1.0 - slippage_tolerance);
-
-//     // === Print the math ===
-//     println!("\n📊 Swap Preview:");
-//     println!(
-//         "  Input:             {:.6} {}",
-//         amount_in, input_token_denom
-//     );
-//     println!(
-//         "  Estimated Output:  {:.6} {}",
-//         estimated_out, output_token_denom
-//     );
-//     println!("  Slippage Tolerance: {:.2}%", slippage_tolerance * 100.0);
-//     println!(
-//         "  Min Output (set in tx): {:.6} {}\n",
-//         min_out, output_token_denom
-//     );
-
-//     // === Build MsgSwapExactAmountIn ===
-//     let token_in_amount = ((amount_in * 1_000_000.0).round()) as u64;
-//     let token_out_min_amount = (min_out * 1_000_000.0).round() as u64;
-
-//     let msg = MsgSwapExactAmountIn {
-//         sender: address.to_string().clone(),
-//         routes: vec![SwapAmountInRoute {
-//             pool_id: pool_id.parse::<u64>()?,
-//             token_out_denom: output_token_denom.to_string(),
-//         }],
-//         token_in: Some(osmosis_std::types::cosmos::base::v1beta1::Coin {
-//             denom: input_token_denom.to_string(),
-//             amount: token_in_amount.to_string(),
-//         }),
-//         token_out_min_amount: token_out_min_amount.to_string(),
-//     };
+pub async fn pool_swap(
+    address: &str,
+    pool_id: &str,
+    input_token_denom: &str,
+    output_token_denom: &str,
+    token_in_amount: u128,
+    token_out_min_amount: u128,
+) -> Result<(u64, u64, ProtoAny), Box<dyn std::error::Error + Send + Sync>> {
+    // === Fetch account_number and sequence ===
+    let client = Client::new();
+    let url = format!(
+        "https://osmosis-api.polkachu.com/cosmos/auth/v1beta1/accounts/{}",
+        address
+    );
+    let res = client.get(&url).send().await?.error_for_status()?;
+    let account_data: AccountWrapper = res.json().await?;
+
+    let account_number = account_data.account.account_number.parse::<u64>()?;
+    let sequence = account_data.account.sequence.parse::<u64>()?;
+
+    // === Build MsgSwapExactAmountIn (amounts in base units) ===
+    let msg = MsgSwapExactAmountIn {
+        sender: address.to_string(),
+        routes: vec![SwapAmountInRoute {
+            pool_id: pool_id.parse::<u64>()?,
+            token_out_denom: output_token_denom.to_string(),
+        }],
+        token_in: Some(ProtoCoin {
+            denom: input_token_denom.to_string(),
+            amount: token_in_amount.to_string(),
+        }),
+        token_out_min_amount: token_out_min_amount.to_string(),
+    };
 
-//     let msg_any = cosmrs::Any {
-//         type_url: "/osmosis.gamm.v1beta1.MsgSwapExactAmountIn".to_string(),
-//         value: msg.encode_to_vec(),
-//     };
-//     Ok((account_number, sequence, msg_any))
-// }
+    let msg_any = ProtoAny {
+        type_url: "/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn".to_string(),
+        value: msg.encode_to_vec(),
+    };
+    Ok((account_number, sequence, msg_any))
+}
 
-// pub async fn sign_tx_broadcast(
-//     msg_any: cosmrs::Any,
-//     public_key: cosmrs::crypto::PublicKey,
-//     sequence: u64,
-//     account_number: u64,
-//     signing_key: &SigningKey,
-// ) -> Result<String, Box<dyn std::error::Error>> {
-//     // === Construct Tx body ===
-//     let tx_body = tx::Body::new(vec![msg_any], "", 0u32);
+/// Signs a one-message tx in SIGN_MODE_DIRECT and returns the `TxRaw` bytes
+pub fn sign_tx(
+    msg_any: ProtoAny,
+    signing_key: &SigningKey,
+    sequence: u64,
+    account_number: u64,
+) -> Vec<u8> {
+    // === Construct Tx body ===
+    let body_bytes = TxBody {
+        messages: vec![msg_any],
+        memo: String::new(),
+        timeout_height: 0,
+    }
+    .encode_to_vec();
 
-//     let fee = Coin {
-//         denom: Denom::from_str("uosmo").unwrap(),
-//         amount: 1000,
-//     };
+    let public_key = ProtoAny {
+        type_url: "/cosmos.crypto.secp256k1.PubKey".to_string(),
+        value: ProtoPubKey {
+            key: signing_key.verifying_key().to_bytes().to_vec(),
+        }
+        .encode_to_vec(),
+    };
+    let auth_info_bytes = AuthInfo {
+        signer_infos: vec![SignerInfo {
+            public_key: Some(public_key),
+            mode_info: Some(ModeInfo {
+                single: Some(ModeInfoSingle {
+                    mode: SIGN_MODE_DIRECT,
+                }),
+            }),
+            sequence,
+        }],
+        fee: Some(Fee {
+            amount: vec![ProtoCoin {
+                denom: "uosmo".to_string(),
+                amount: OSMOSIS_FEE_UOSMO.to_string(),
+            }],
+            gas_limit: OSMOSIS_GAS_LIMIT,
+            payer: String::new(),
+            granter: String::new(),
+        }),
+    }
+    .encode_to_vec();
 
-//     let gas_limit: u64 = 200_000;
-//     let signer_info = SignerInfo::single_direct(Some(public_key), sequence);
-//     let fee = Fee::from_amount_and_gas(fee, gas_limit);
-//     let auth_info = tx::AuthInfo {
-//         signer_infos: vec![signer_info],
-//         fee,
-//     };
+    let sign_doc = SignDoc {
+        body_bytes: body_bytes.clone(),
+        auth_info_bytes: auth_info_bytes.clone(),
+        chain_id: OSMOSIS_CHAIN_ID.to_string(),
+        account_number,
+    };
+    let signature: CosmosSignature = signing_key.sign(&sign_doc.encode_to_vec());
 
-//     let sign_doc = SignDoc::new(
-//         &tx_body,
-//         &auth_info,
-//         &cosmrs::tendermint::chain::Id::from_str("osmosis-1").unwrap(),
-//         account_number,
-//     )?;
-//     let tx_raw = sign_doc.sign(&signing_key)?;
+    TxRaw {
+        body_bytes,
+        auth_info_bytes,
+        signatures: vec![signature.to_bytes().to_vec()],
+    }
+    .encode_to_vec()
+}
 
-//     // === Broadcast transaction ===
-//     let tx_bytes = tx_raw.to_bytes()?;
-//     let base64_tx = general_purpose::STANDARD.encode(tx_bytes);
-//     println!("Broadcast result: {}", base64_tx);
+/// Hash the chain indexes a tx under: uppercase hex SHA-256 of its bytes
+pub fn tx_hash(tx_bytes: &[u8]) -> String {
+    Sha256::digest(tx_bytes)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
 
-//     let res = reqwest::Client::new()
-//         .post("https://osmosis-api.polkachu.com/cosmos/tx/v1beta1/txs")
-//         .json(&serde_json::json!({
-//             "tx_bytes": base64_tx,
-//             "mode": "BROADCAST_MODE_SYNC"
-//         }))
-//         .send()
-//         .await?;
+/// Signs and broadcasts the swap. A tx the node refuses is an error; a broadcast that gets
+/// no answer still returns the hash, since the tx may have reached the mempool.
+pub async fn sign_tx_broadcast(
+    msg_any: ProtoAny,
+    signing_key: &SigningKey,
+    sequence: u64,
+    account_number: u64,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let tx_bytes = sign_tx(msg_any, signing_key, sequence, account_number);
+    let txhash = tx_hash(&tx_bytes);
+
+    // === Broadcast transaction ===
+    let base64_tx = general_purpose::STANDARD.encode(&tx_bytes);
+    let res = reqwest::Client::new()
+        .post("https://osmosis-api.polkachu.com/cosmos/tx/v1beta1/txs")
+        .json(&serde_json::json!({
+            "tx_bytes": base64_tx,
+            "mode": "BROADCAST_MODE_SYNC"
+        }))
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
 
-//     let response_json: serde_json::Value = res.json().await?;
-//     let txhash = response_json["tx_response"]["txhash"]
-//         .as_str()
-//         .ok_or("Missing txhash in response")?
-//         .to_string();
+    let response_json: Value = match res {
+        Ok(res) => res.json().await.unwrap_or_default(),
+        Err(e) if e.status().is_some_and(|status| status.is_client_error()) => {
+            return Err(e.into());
+        }
+        Err(e) => {
+            println!("⚠️ No answer to the broadcast of {}: {}", txhash, e);
+            return Ok(txhash);
+        }
+    };
 
-//     println!("✅ Broadcast txhash: {}", txhash);
-//     Ok(txhash)
-// }
+    let code = response_json["tx_response"]["code"].as_u64().unwrap_or(0);
+    if code != 0 {
+        let raw_log = response_json["tx_response"]["raw_log"]
+            .as_str()
+            .unwrap_or_default();
+        return Err(Box::new(BotError::classify(&format!(
+            "Tx {} refused with code {}: {}",
+            txhash, code, raw_log
+        ))));
+    }
+
+    println!("✅ Broadcast txhash: {}", txhash);
+    Ok(txhash)
+}
+
+/// Amount of a coin string such as `1000000uosmo`, in base units
+fn coin_amount(coin: &str) -> Option<f64> {
+    let digits = coin
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(coin.len());
+    coin[..digits].parse().ok()
+}
 
+/// Outcome of an indexed tx: success, the failure log and the swapped
+/// `(tokens_in, tokens_out)` in base units. `None` while the tx is not indexed.
 pub async fn check_tx_success(
     txhash: &str,
-) -> Result<Option<(bool, Option<String>, Option<(f64, f64)>)>, Box<dyn std::error::Error>> {
+) -> Result<
+    Option<(bool, Option<String>, Option<(f64, f64)>)>,
+    Box<dyn std::error::Error + Send + Sync>,
+> {
     let url = format!(
         "https://osmosis-api.polkachu.com/cosmos/tx/v1beta1/txs/{}",
         txhash
@@ -342,16 +520,11 @@ pub async fn check_tx_success(
                 let vec_a = vec![];
                 let attrs = event["attributes"].as_array().unwrap_or(&vec_a);
                 for attr in attrs {
+                    let val = attr["value"].as_str().unwrap_or_default();
                     if attr["key"] == "tokens_in" {
-                        let val = attr["value"].as_str().unwrap_or_default();
-                        if let Some(num) = val.split("uosmo").next() {
-                            tokens_in = Some(num.parse::<f64>().unwrap_or(0.0) / 1_000_000.0);
-                        }
+                        tokens_in = coin_amount(val);
                     } else if attr["key"] == "tokens_out" {
-                        let val = attr["value"].as_str().unwrap_or_default();
-                        if let Some(num) = val.split("ibc/").next() {
-                            tokens_out = Some(num.parse::<f64>().unwrap_or(0.0) / 1_000_000.0);
-                        }
+                        tokens_out = coin_amount(val);
                     }
                 }
             }
@@ -360,45 +533,40 @@ pub async fn check_tx_success(
 
     if code == 0 {
         println!("✅ Tx {} successful", txhash);
-        return Ok(Some((
-            true,
-            None,
-            Some((tokens_in.unwrap_or(0.0), tokens_out.unwrap_or(0.0))),
-        )));
+        Ok(Some((true, None, tokens_in.zip(tokens_out))))
     } else {
         println!("❌ Tx {} failed with code {}: {}", txhash, code, raw_log);
-        return Ok(Some((false, Some(raw_log), None)));
+        Ok(Some((false, Some(raw_log), None)))
     }
 }
 
+/// Polls `check_tx_success` until the tx is indexed; `None` if it never shows up.
+/// Lookup errors are retried: the tx may have landed either way.
 pub async fn wait_for_tx_confirmation(
     txhash: &str,
     max_attempts: u32,
     delay_secs: u64,
-) -> Result<(bool, Option<String>, Option<(f64, f64)>), Box<dyn std::error::Error>> {
+) -> Option<(bool, Option<String>, Option<(f64, f64)>)> {
     for attempt in 1..=max_attempts {
         println!(
             "⏳ Checking tx ({}) attempt {}/{}...",
             txhash, attempt, max_attempts
         );
 
-        match check_tx_success(txhash).await? {
-            Some((true, _, Some(amounts))) => return Ok((true, None, Some(amounts))),
-            Some((true, _, None)) => return Ok((true, None, None)), // <-- Handle success without amounts
-            Some((false, Some(log), _)) => return Ok((false, Some(log), None)),
-            Some((false, None, _)) => return Ok((false, Some("Unknown failure".into()), None)),
-            None => {
-                sleep(Duration::from_secs(delay_secs)).await;
+        match check_tx_success(txhash).await {
+            Ok(Some((true, _, amounts))) => return Some((true, None, amounts)),
+            Ok(Some((false, Some(log), _))) => return Some((false, Some(log), None)),
+            Ok(Some((false, None, _))) => {
+                return Some((false, Some("Unknown failure".into()), None));
             }
+            Ok(None) => {}
+            Err(e) => println!("⚠️ Tx {} lookup failed: {}", txhash, e),
         }
+        sleep(Duration::from_secs(delay_secs)).await;
     }
 
     println!("❌ Timed out waiting for tx confirmation.");
-    Ok((
-        false,
-        Some("Timeout: tx not confirmed within expected time".into()),
-        None,
-    ))
+    None
 }
 
 pub fn simulate_swap_math(amount_in: f64, reserve_in: f64, reserve_out: f64, fee: f64) -> f64 {
@@ -477,39 +645,34 @@ pub fn simulate_swap_math(amount_in: f64, reserve_in: f64, reserve_out: f64, fee
 // SOLANA UTILS START
 
 pub async fn sol_get_sol_balance(
-    rpc_url: &str,
+    rpc: &Arc<RpcPool>,
     wallet_pubkey: &Pubkey,
-) -> Result<f64, Box<dyn std::error::Error>> {
-    let client = RpcClient::new(rpc_url.to_string());
+) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+    let client = rpc.rpc_client();
 
     let lamports = client.get_balance(wallet_pubkey).await?;
     println!("🪙 Raw lamports: {}", lamports);
 
-    let sol = lamports_to_sol(lamports);
+    let sol = token_registry()
+        .to_ui_amount(SOL_MINT, lamports as f64)
+        .await?;
     Ok(sol)
 }
 
-pub async fn get_usdc_balance(wallet_address: &str, usdc_mint: &str) -> f64 {
-    // USDC Mint on Solana mainnet
-    let rpc_url = "https://api.mainnet-beta.solana.com";
+pub async fn get_usdc_balance(
+    rpc: &RpcPool,
+    wallet_address: &str,
+    usdc_mint: &str,
+) -> Result<f64, Box<dyn StdError + Send + Sync>> {
+    let params = serde_json::json!([
+        wallet_address,
+        { "mint": usdc_mint },
+        { "encoding": "jsonParsed" }
+    ]);
 
-    let body = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "getTokenAccountsByOwner",
-        "params": [
-            wallet_address,
-            { "mint": usdc_mint },
-            { "encoding": "jsonParsed" }
-        ]
-    });
+    let result = rpc.request("getTokenAccountsByOwner", params).await?;
 
-    let client = Client::new();
-    let res = client.post(rpc_url).json(&body).send().await.unwrap();
-
-    let json: serde_json::Value = res.json().await.unwrap();
-
-    let accounts = json["result"]["value"].as_array().ok_or("No accounts found").unwrap();
+    let accounts = result["value"].as_array().ok_or("No accounts found")?;
 
     let mut total_usdc = 0.0;
     for account in accounts {
@@ -520,32 +683,24 @@ pub async fn get_usdc_balance(wallet_address: &str, usdc_mint: &str) -> f64 {
         }
     }
 
-    total_usdc
+    Ok(total_usdc)
 }
 
-pub async fn jupiter_swap(
-    rpc_url: &str,
+/// Fetches a Jupiter quote, returning the expected output (UI units) and the raw quote
+pub async fn jupiter_quote(
     input_mint: &str,
     output_mint: &str,
     amount: f64,
     slippage_bps: u64,
-    user_keypair: &Keypair,
-) -> Result<(f64, String), Box<dyn StdError>> {
-    let user_pubkey = user_keypair.pubkey();
+) -> Result<(f64, serde_json::Value), Box<dyn StdError + Send + Sync>> {
     let client = Client::new();
 
-    // Convert amount based on input token decimals
-    let input_decimals = if input_mint == "So11111111111111111111111111111111111111112" {
-        9 // SOL
-    } else {
-        6 // USDC or others
-    };
-    let amount_in_ui_units = token_amount_to_ui_units(amount, input_decimals);
+    let registry = token_registry();
+    let amount_in_base_units = registry.to_base_units(input_mint, amount).await?;
 
-    // === 1. Fetch quote
     let quote_url = format!(
         "https://quote-api.jup.ag/v6/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-        input_mint, output_mint, amount_in_ui_units, slippage_bps
+        input_mint, output_mint, amount_in_base_units, slippage_bps
     );
 
     let quote_resp = client.get(&quote_url).send().await?.error_for_status()?;
@@ -558,13 +713,25 @@ pub async fn jupiter_swap(
         .ok_or("Missing outAmount")?
         .parse::<f64>()?;
 
-    // Convert based on output token decimals
-    let output_decimals = if output_mint == "So11111111111111111111111111111111111111112" {
-        9 // SOL
-    } else {
-        6 // USDC or others
-    };
-    let out_amount = out_amount_raw / 10f64.powi(output_decimals as i32);
+    let out_amount = registry.to_ui_amount(output_mint, out_amount_raw).await?;
+
+    Ok((out_amount, quote_json))
+}
+
+pub async fn jupiter_swap(
+    rpc: &Arc<RpcPool>,
+    input_mint: &str,
+    output_mint: &str,
+    amount: f64,
+    slippage_bps: u64,
+    user_keypair: &Keypair,
+) -> Result<(f64, String), Box<dyn StdError + Send + Sync>> {
+    let user_pubkey = user_keypair.pubkey();
+    let client = Client::new();
+
+    // === 1. Fetch quote
+    let (out_amount, quote_json) =
+        jupiter_quote(input_mint, output_mint, amount, slippage_bps).await?;
 
     // === 2. Build swap request using full quote
     let swap_body = serde_json::json!({
@@ -588,33 +755,96 @@ pub async fn jupiter_swap(
         .as_str()
         .ok_or("Missing swapTransaction field")?;
 
-    // === 4. Decode, sign, and send transaction
-    let tx_bytes = base64::decode(tx_base64)?;
+    // === 4. Decode and sign transaction
+    let tx_bytes = general_purpose::STANDARD.decode(tx_base64)?;
     let mut tx: VersionedTransaction = bincode::deserialize(&tx_bytes)?;
-    let sig = user_keypair.sign_message(&tx.message.serialize());
-    tx.signatures[0] = sig;
-
-    let rpc = RpcClient::new(rpc_url.to_string());
-    let tx_signature = rpc.send_and_confirm_transaction(&tx).await?;
+    let tx_signature = user_keypair.sign_message(&tx.message.serialize());
+    tx.signatures[0] = tx_signature;
+
+    // === 5. Send and confirm transaction
+    let client = rpc.rpc_client();
+    match client.send_transaction(&tx).await {
+        Ok(_) => println!("📨 Swap sent! Signature: {}", tx_signature),
+        // The RPC answered with an error (preflight failure, rate limit, ...): nothing was sent
+        Err(e)
+            if e.get_transaction_error().is_some()
+                || matches!(
+                    e.kind(),
+                    ClientErrorKind::RpcError(RpcError::RpcResponseError { .. })
+                ) =>
+        {
+            return Err(e.into());
+        }
+        // No answer: the transaction may have been forwarded anyway
+        Err(e) => println!(
+            "⚠️ No answer sending swap {}, checking whether it landed: {}",
+            tx_signature, e
+        ),
+    }
+    confirm_swap(&client, &tx, &tx_signature).await?;
 
-    println!("✅ Swap submitted! Signature: {}", tx_signature);
+    println!("✅ Swap confirmed! Signature: {}", tx_signature);
 
-    // Return out_amount in proper units (SOL or USDC), and tx signature
+    // Return out_amount in UI units of the output mint, and tx signature
     Ok((out_amount, tx_signature.to_string()))
 }
 
-fn token_amount_to_ui_units(amount: f64, decimals: u8) -> u64 {
-    (amount * 10_f64.powi(decimals as i32)) as u64
+/// Waits for a sent swap to be confirmed. It is only reported as not filled once it failed
+/// on chain or its blockhash expired without it landing; otherwise the outcome is unknown
+/// and `BotError::Unconfirmed` keeps it from being retried.
+async fn confirm_swap(
+    client: &RpcClient,
+    tx: &VersionedTransaction,
+    tx_signature: &Signature,
+) -> Result<(), Box<dyn StdError + Send + Sync>> {
+    let commitment = CommitmentConfig::confirmed();
+    for _ in 0..SWAP_CONFIRM_POLLS {
+        sleep(Duration::from_secs(2)).await;
+        let status = match client.get_signature_statuses(&[*tx_signature]).await {
+            Ok(statuses) => statuses.value.into_iter().next().flatten(),
+            Err(e) => {
+                println!("⚠️ Failed to check swap {}: {}", tx_signature, e);
+                continue;
+            }
+        };
+        if let Some(status) = status {
+            if let Some(err) = status.err.clone() {
+                return Err(ClientError::from(err).into());
+            }
+            if status.satisfies_commitment(commitment) {
+                return Ok(());
+            }
+            continue; // Landed, not confirmed yet
+        }
+        // Past its blockhash the transaction can no longer land
+        if let Ok(false) = client
+            .is_blockhash_valid(tx.message.recent_blockhash(), commitment)
+            .await
+            && let Ok(statuses) = client.get_signature_statuses(&[*tx_signature]).await
+            && statuses.value.first().is_some_and(Option::is_none)
+        {
+            return Err(
+                BotError::Network(format!("Swap {} expired before landing", tx_signature)).into(),
+            );
+        }
+    }
+    Err(BotError::Unconfirmed(format!(
+        "could not tell whether swap {} landed; check the wallet and /reconcile before restarting",
+        tx_signature
+    ))
+    .into())
 }
 
-pub async fn run_jupiter_bot(left_asset: &str, right_asset: &str, sell_percentage: f64, dca_recover_percentage: f64, r_factor: f64, trading_flag: std::sync::Arc<tokio::sync::Mutex<bool>>) {
-    crate::jupiter_strategy_start::jup_bot_start(
-        left_asset,
-        right_asset,
-        sell_percentage,
-        dca_recover_percentage,
-        r_factor,
-        trading_flag
-    )
-    .await;
+pub async fn run_jupiter_bot(
+    config: &crate::config::StrategyConfig,
+    trading_flag: std::sync::Arc<tokio::sync::Mutex<bool>>,
+) {
+    let venue = match crate::swap_venue::build_venue(config) {
+        Ok(venue) => venue,
+        Err(e) => {
+            eprintln!("❌ Failed to set up venue for {}: {}", config.pair_name(), e);
+            return;
+        }
+    };
+    crate::jupiter_strategy_start::jup_bot_start(config, venue.as_ref(), trading_flag).await;
 }
\ No newline at end of file